@@ -0,0 +1,94 @@
+#[macro_use]
+extern crate criterion;
+extern crate uniparc_xml_parser;
+
+use std::fs;
+use std::io;
+
+use criterion::{Criterion, Throughput};
+use uniparc_xml_parser::{
+    parse_entry, parse_entry_ref, run, CsvFormat, EntrySplitter, ParseOptions, RawEntry,
+    RunOptions, SchemaOptions, Sink, TsvSink,
+};
+
+/// Raw entries of the sample file, repeated to get a reasonably sized input.
+fn sample_entries() -> Vec<RawEntry> {
+    let sample = fs::read("tests/data/uniparc_sample.xml").unwrap();
+    let entries = EntrySplitter::new(&sample[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    entries.iter().cycle().take(3_000).cloned().collect()
+}
+
+fn parse(c: &mut Criterion) {
+    let entries = sample_entries();
+    let num_bytes = entries.iter().map(|e| e.data.len() as u64).sum();
+
+    let mut group = c.benchmark_group("parse_and_write_tsv");
+    group.throughput(Throughput::Bytes(num_bytes));
+    group.bench_function("owned", |b| {
+        let mut sink = TsvSink::new(
+            |_| Ok(io::sink()),
+            CsvFormat::default(),
+            SchemaOptions::default(),
+        )
+        .unwrap();
+        b.iter(|| {
+            for raw_entry in &entries {
+                let entry = parse_entry(raw_entry, &ParseOptions::default()).unwrap();
+                sink.on_entry(&entry).unwrap();
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        let mut sink = TsvSink::new(
+            |_| Ok(io::sink()),
+            CsvFormat::default(),
+            SchemaOptions::default(),
+        )
+        .unwrap();
+        b.iter(|| {
+            for raw_entry in &entries {
+                let entry = parse_entry_ref(raw_entry, &ParseOptions::default()).unwrap();
+                sink.on_entry_ref(&entry).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+/// Compare the main loop on a single thread, where entries are written without being copied,
+/// to the main loop on several threads, where each entry is copied before it is written.
+fn run_threads(c: &mut Criterion) {
+    let entries = sample_entries();
+    let mut input = b"<uniparc>\n".to_vec();
+    for raw_entry in &entries {
+        input.extend_from_slice(&raw_entry.data);
+        input.push(b'\n');
+    }
+    input.extend_from_slice(b"</uniparc>\n");
+
+    let mut group = c.benchmark_group("run_tsv");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    for &threads in &[1, 4] {
+        group.bench_function(format!("threads_{}", threads), |b| {
+            b.iter(|| {
+                let sink = TsvSink::new(
+                    |_| Ok(io::sink()),
+                    CsvFormat::default(),
+                    SchemaOptions::default(),
+                )
+                .unwrap();
+                let options = RunOptions {
+                    threads,
+                    ..RunOptions::default()
+                };
+                run(&input[..], sink, options).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, run_threads);
+criterion_main!(benches);
@@ -7,6 +7,11 @@ CREATE TABLE uniparc (
 	sequence_checksum varchar NOT NULL
 );
 
+-- Digests enabled with --digests are written after the other columns, in this order:
+-- ALTER TABLE uniparc ADD COLUMN sequence_md5 varchar;
+-- ALTER TABLE uniparc ADD COLUMN sequence_sha256 varchar;
+-- ALTER TABLE uniparc ADD COLUMN sequence_refget varchar;
+
 CREATE TABLE domain (
 	uniparc_id varchar NOT NULL REFERENCES uniparc (uniparc_id),
 	database varchar NOT NULL,
@@ -25,11 +30,11 @@ CREATE TABLE xref (
 	xref_id bigint NOT NULL,
 	db_type varchar NOT NULL,
 	db_id varchar NOT NULL,
-	version_i varchar NOT NULL,
-	active varchar NOT NULL,
-	version varchar NOT NULL,
-	created varchar NOT NULL,
-	last varchar NOT NULL,
+	version_i integer NOT NULL,
+	active boolean NOT NULL,
+	version integer,
+	created date NOT NULL,
+	last date NOT NULL,
 	PRIMARY KEY (uniparc_id, xref_id)
 );
 
@@ -68,6 +73,10 @@ CREATE TABLE ncbi_taxonomy_id (
 	FOREIGN KEY (uniparc_id, xref_id) REFERENCES xref (uniparc_id, xref_id)
 );
 
+-- Columns written with --taxon-lineage:
+-- ALTER TABLE ncbi_taxonomy_id ADD COLUMN rank varchar;
+-- ALTER TABLE ncbi_taxonomy_id ADD COLUMN lineage varchar;
+
 CREATE TABLE pdb_chain (
 	uniparc_id varchar NOT NULL,
 	xref_id bigint NOT NULL,
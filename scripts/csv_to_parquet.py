@@ -1,5 +1,5 @@
 from pathlib import Path
-from typing import List
+from typing import List, Optional
 
 import pyarrow as pa
 import pyarrow.parquet as pq
@@ -12,7 +12,7 @@ def csv_to_parquet(
     parquet_file: Path,
     *,
     delimiter: str,
-    column_names: List[str],
+    column_names: Optional[List[str]],
     quiet: bool = False,
 ) -> None:
     block_size = 1 << 24  # 16 MB
@@ -36,7 +36,12 @@ if __name__ == "__main__":
 
     parser = argparse.ArgumentParser()
     parser.add_argument("-f", "--input-file", help="input CSV file")
-    parser.add_argument("-c", "--column-names", help="names of columns")
+    parser.add_argument(
+        "-c",
+        "--column-names",
+        default=None,
+        help="names of columns (read from the header row if not provided)",
+    )
     parser.add_argument("-d", "--delimiter", default="\t", help="delimiter used by the CSV file")
     parser.add_argument("-o", "--output-file", default=None, help="output Parquer file")
     parser.add_argument("-q", "--quiet", action="store_true", help="disable progressbar")
@@ -49,7 +54,7 @@ if __name__ == "__main__":
         else csv_file.with_suffix(".parquet")
     )
     delimiter = args.delimiter
-    column_names = args.column_names.split(",")
+    column_names = args.column_names.split(",") if args.column_names is not None else None
 
     csv_to_parquet(
         csv_file,
@@ -0,0 +1,350 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, Int32Builder, Int64Builder, StringBuilder, StructBuilder,
+};
+use arrow_array::types::Date32Type;
+use arrow_array::RecordBatch;
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
+
+use model::{Uniparc, UniparcDomain, UniparcEntry, UniparcProperty, UniparcXRef};
+use properties::Properties;
+use schema::{table_schemas, Column, ColumnType, Record, SchemaOptions};
+
+/// Trait which means that a struct can be stored as a row of an Arrow `RecordBatch`.
+pub trait ArrowRecord: Record {
+    /// Append this record to a builder for its table, which has the optional columns given by
+    /// `schema` after the columns of `fields()`.
+    fn append_to(&self, builder: &mut StructBuilder, schema: &SchemaOptions);
+
+    /// Arrow fields corresponding to the columns of the table.
+    fn fields() -> Fields {
+        Self::columns().iter().map(arrow_field).collect()
+    }
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(Self::fields()))
+    }
+}
+
+fn arrow_field(column: &Column) -> Field {
+    let data_type = match column.column_type {
+        ColumnType::String => DataType::Utf8,
+        ColumnType::Int32 => DataType::Int32,
+        ColumnType::Int64 => DataType::Int64,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Date => DataType::Date32,
+    };
+    Field::new(column.name, data_type, column.nullable)
+}
+
+fn string_field(builder: &mut StructBuilder, i: usize) -> &mut StringBuilder {
+    builder.field_builder::<StringBuilder>(i).unwrap()
+}
+
+fn int32_field(builder: &mut StructBuilder, i: usize) -> &mut Int32Builder {
+    builder.field_builder::<Int32Builder>(i).unwrap()
+}
+
+fn int64_field(builder: &mut StructBuilder, i: usize) -> &mut Int64Builder {
+    builder.field_builder::<Int64Builder>(i).unwrap()
+}
+
+fn boolean_field(builder: &mut StructBuilder, i: usize) -> &mut BooleanBuilder {
+    builder.field_builder::<BooleanBuilder>(i).unwrap()
+}
+
+fn date_field(builder: &mut StructBuilder, i: usize) -> &mut Date32Builder {
+    builder.field_builder::<Date32Builder>(i).unwrap()
+}
+
+impl ArrowRecord for Uniparc {
+    fn append_to(&self, builder: &mut StructBuilder, schema: &SchemaOptions) {
+        string_field(builder, 0).append_value(&self.uniparc_id);
+        string_field(builder, 1).append_value(&self.sequence);
+        int32_field(builder, 2).append_value(self.sequence_length as i32);
+        string_field(builder, 3).append_value(&self.sequence_checksum);
+        let digests = [
+            (schema.digests.md5, &self.sequence_md5),
+            (schema.digests.sha256, &self.sequence_sha256),
+            (schema.digests.refget, &self.sequence_refget),
+        ];
+        let enabled = digests.iter().filter(|&&(enabled, _)| enabled);
+        for (i, &(_, digest)) in enabled.enumerate() {
+            string_field(builder, 4 + i).append_option(digest.as_ref());
+        }
+        builder.append(true);
+    }
+}
+
+impl ArrowRecord for UniparcDomain {
+    fn append_to(&self, builder: &mut StructBuilder, _: &SchemaOptions) {
+        string_field(builder, 0).append_value(&self.uniparc_id);
+        string_field(builder, 1).append_value(&self.database);
+        string_field(builder, 2).append_value(&self.database_id);
+        string_field(builder, 3).append_value(&self.interpro_name);
+        string_field(builder, 4).append_value(&self.interpro_id);
+        int32_field(builder, 5).append_value(self.domain_start as i32);
+        int32_field(builder, 6).append_value(self.domain_end as i32);
+        builder.append(true);
+    }
+}
+
+impl ArrowRecord for UniparcXRef {
+    fn append_to(&self, builder: &mut StructBuilder, _: &SchemaOptions) {
+        string_field(builder, 0).append_value(&self.uniparc_id);
+        int64_field(builder, 1).append_value(self.xref_id as i64);
+        string_field(builder, 2).append_value(self.db_type.name());
+        string_field(builder, 3).append_value(&self.db_id);
+        int32_field(builder, 4).append_value(self.version_i as i32);
+        boolean_field(builder, 5).append_value(self.active);
+        int32_field(builder, 6).append_option(self.version.map(|v| v as i32));
+        date_field(builder, 7).append_value(Date32Type::from_naive_date(self.created));
+        date_field(builder, 8).append_value(Date32Type::from_naive_date(self.last));
+        builder.append(true);
+    }
+}
+
+impl ArrowRecord for UniparcProperty {
+    fn append_to(&self, builder: &mut StructBuilder, schema: &SchemaOptions) {
+        string_field(builder, 0).append_value(&self.uniparc_id);
+        int64_field(builder, 1).append_value(self.xref_id as i64);
+        string_field(builder, 2).append_value(&self.property);
+        string_field(builder, 3).append_value(&self.value);
+        if schema.has_taxonomy_columns(&self.table) {
+            string_field(builder, 4).append_option(self.rank.as_ref());
+            string_field(builder, 5).append_option(self.lineage.as_ref());
+        }
+        builder.append(true);
+    }
+}
+
+/// Accumulates records into `RecordBatch`es of a fixed number of rows.
+pub struct BatchBuilder<T: ArrowRecord> {
+    builder: StructBuilder,
+    schema: SchemaOptions,
+    batch_size: usize,
+    num_rows: usize,
+    _record: PhantomData<T>,
+}
+
+impl<T: ArrowRecord> BatchBuilder<T> {
+    /// Create a builder for a table with the columns of `T`, without any optional columns.
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn new(batch_size: usize) -> BatchBuilder<T> {
+        BatchBuilder::with_columns(&T::columns(), SchemaOptions::default(), batch_size)
+    }
+
+    /// Create a builder for a table with `columns` instead of the columns of `T`.
+    ///
+    /// The columns have to be the columns of `T` followed by the optional columns given by
+    /// `schema`, which `T::append_to` fills.
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn with_columns(
+        columns: &[Column],
+        schema: SchemaOptions,
+        batch_size: usize,
+    ) -> BatchBuilder<T> {
+        assert!(batch_size > 0, "batch size must be greater than 0");
+        let fields = columns.iter().map(arrow_field).collect::<Fields>();
+        BatchBuilder {
+            builder: StructBuilder::from_fields(fields, batch_size),
+            schema,
+            batch_size,
+            num_rows: 0,
+            _record: PhantomData,
+        }
+    }
+
+    /// Add a record, returning a `RecordBatch` if the batch is full.
+    pub fn push(&mut self, record: &T) -> Option<RecordBatch> {
+        record.append_to(&mut self.builder, &self.schema);
+        self.num_rows += 1;
+        if self.num_rows >= self.batch_size {
+            self.finish()
+        } else {
+            None
+        }
+    }
+
+    /// Return a `RecordBatch` with all remaining records, if there are any.
+    pub fn finish(&mut self) -> Option<RecordBatch> {
+        if self.num_rows == 0 {
+            return None;
+        }
+        self.num_rows = 0;
+        Some(RecordBatch::from(self.builder.finish()))
+    }
+}
+
+fn property_builder(
+    table: &str,
+    schema: &SchemaOptions,
+    batch_size: usize,
+) -> BatchBuilder<UniparcProperty> {
+    BatchBuilder::with_columns(&schema.property_columns(table), schema.clone(), batch_size)
+}
+
+/// Splits the data extracted from UniParc entries into `RecordBatch`es for each output table.
+///
+/// ```no_run
+/// use std::io::{self, BufReader};
+/// use uniparc_xml_parser::{EntryBatcher, UniparcReader};
+///
+/// let mut batcher = EntryBatcher::new(8192);
+/// for entry in UniparcReader::new(BufReader::new(io::stdin())) {
+///     for (table, batch) in batcher.push(&entry.unwrap()) {
+///         println!("{}: {} rows", table, batch.num_rows());
+///     }
+/// }
+/// for (table, batch) in batcher.finish() {
+///     println!("{}: {} rows", table, batch.num_rows());
+/// }
+/// ```
+pub struct EntryBatcher {
+    uniparc: BatchBuilder<Uniparc>,
+    domain: BatchBuilder<UniparcDomain>,
+    xref: BatchBuilder<UniparcXRef>,
+    properties: Properties<BatchBuilder<UniparcProperty>>,
+    /// Builders for property tables other than the built-in ones, created as they are needed.
+    extra_properties: BTreeMap<String, BatchBuilder<UniparcProperty>>,
+    batch_size: usize,
+    schema: SchemaOptions,
+}
+
+/// Name of an output table together with a batch of its rows.
+pub type TableBatch = (Cow<'static, str>, RecordBatch);
+
+impl EntryBatcher {
+    /// Create a batcher which yields `RecordBatch`es of `batch_size` rows, without any optional
+    /// columns.
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn new(batch_size: usize) -> EntryBatcher {
+        EntryBatcher::with_schema(SchemaOptions::default(), batch_size)
+    }
+
+    /// Create a batcher which yields `RecordBatch`es of `batch_size` rows, with the optional
+    /// columns given by `schema`.
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn with_schema(schema: SchemaOptions, batch_size: usize) -> EntryBatcher {
+        EntryBatcher {
+            uniparc: BatchBuilder::with_columns(
+                &schema.uniparc_columns(),
+                schema.clone(),
+                batch_size,
+            ),
+            domain: BatchBuilder::new(batch_size),
+            xref: BatchBuilder::new(batch_size),
+            properties: Properties::from_fn(|name| property_builder(name, &schema, batch_size)),
+            extra_properties: BTreeMap::new(),
+            batch_size,
+            schema,
+        }
+    }
+
+    /// Names and schemas of all output tables.
+    pub fn schemas(&self) -> Vec<(&'static str, SchemaRef)> {
+        table_schemas(&self.schema)
+            .iter()
+            .map(|table| {
+                let fields = table.columns.iter().map(arrow_field).collect::<Fields>();
+                (table.name, Arc::new(Schema::new(fields)))
+            })
+            .collect()
+    }
+
+    /// Add a row to the `uniparc` table, returning the batch if it has been filled.
+    pub fn push_uniparc(&mut self, uniparc: &Uniparc) -> Option<TableBatch> {
+        self.uniparc
+            .push(uniparc)
+            .map(|batch| ("uniparc".into(), batch))
+    }
+
+    /// Add a row to the `xref` table, returning the batch if it has been filled.
+    pub fn push_xref(&mut self, xref: &UniparcXRef) -> Option<TableBatch> {
+        self.xref.push(xref).map(|batch| ("xref".into(), batch))
+    }
+
+    /// Add a row to the `domain` table, returning the batch if it has been filled.
+    pub fn push_domain(&mut self, domain: &UniparcDomain) -> Option<TableBatch> {
+        self.domain
+            .push(domain)
+            .map(|batch| ("domain".into(), batch))
+    }
+
+    /// Add a row to the table of `property.table`, returning the batch if it has been filled.
+    pub fn push_property(&mut self, property: &UniparcProperty) -> Option<TableBatch> {
+        let table = &*property.table;
+        let mut builders = self.properties.named_mut();
+        if let Some(&mut (name, ref mut builder)) =
+            builders.iter_mut().find(|&&mut (name, _)| name == table)
+        {
+            return builder.push(property).map(|batch| (name.into(), batch));
+        }
+        let (schema, batch_size) = (&self.schema, self.batch_size);
+        self.extra_properties
+            .entry(table.to_string())
+            .or_insert_with(|| property_builder(table, schema, batch_size))
+            .push(property)
+            .map(|batch| (table.to_string().into(), batch))
+    }
+
+    /// Add the rows extracted from `entry`, returning any batches that have been filled.
+    pub fn push(&mut self, entry: &UniparcEntry) -> Vec<TableBatch> {
+        let mut batches = Vec::new();
+        batches.extend(self.push_uniparc(&entry.uniparc));
+        for domain in &entry.domains {
+            batches.extend(self.push_domain(domain));
+        }
+        for xref in &entry.xrefs {
+            batches.extend(self.push_xref(xref));
+        }
+        let mut builders = self.properties.named_mut();
+        let properties = entry.properties.named();
+        for (&mut (name, ref mut builder), &(_, properties)) in
+            builders.iter_mut().zip(properties.iter())
+        {
+            for property in properties {
+                if let Some(batch) = builder.push(property) {
+                    batches.push((name.into(), batch));
+                }
+            }
+        }
+        for property in &entry.extra_properties {
+            batches.extend(self.push_property(property));
+        }
+        batches
+    }
+
+    /// Return batches with all remaining rows.
+    pub fn finish(&mut self) -> Vec<TableBatch> {
+        let mut batches = Vec::new();
+        if let Some(batch) = self.uniparc.finish() {
+            batches.push(("uniparc".into(), batch));
+        }
+        if let Some(batch) = self.domain.finish() {
+            batches.push(("domain".into(), batch));
+        }
+        if let Some(batch) = self.xref.finish() {
+            batches.push(("xref".into(), batch));
+        }
+        for &mut (name, ref mut builder) in self.properties.named_mut().iter_mut() {
+            if let Some(batch) = builder.finish() {
+                batches.push((name.into(), batch));
+            }
+        }
+        for (name, builder) in &mut self.extra_properties {
+            if let Some(batch) = builder.finish() {
+                batches.push((name.clone().into(), batch));
+            }
+        }
+        batches
+    }
+}
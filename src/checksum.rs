@@ -0,0 +1,91 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use sha2::{Sha256, Sha512};
+
+/// Reversed polynomial of the CRC64 checksum used by UniProt (x^64 + x^4 + x^3 + x + 1).
+const CRC64_POLYNOMIAL: u64 = 0xd800_0000_0000_0000;
+
+const fn crc64_table() -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                (value >> 1) ^ CRC64_POLYNOMIAL
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+static CRC64_TABLE: [u64; 256] = crc64_table();
+
+/// CRC64 checksum of `sequence`, as used for the `checksum` attribute of `<sequence>`.
+pub fn crc64(sequence: &[u8]) -> u64 {
+    sequence.iter().fold(0, |crc, &b| {
+        CRC64_TABLE[((crc ^ u64::from(b)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// CRC64 checksum of `sequence`, formatted as 16 upper-case hexadecimal digits.
+pub fn crc64_hex(sequence: &[u8]) -> String {
+    format!("{:016X}", crc64(sequence))
+}
+
+/// Digests of each sequence which are computed and written to the `uniparc` table.
+///
+/// Only enabled digests have a column in the `uniparc` table.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SequenceDigests {
+    /// MD5 digest, in the `sequence_md5` column.
+    pub md5: bool,
+    /// SHA-256 digest, in the `sequence_sha256` column.
+    pub sha256: bool,
+    /// GA4GH refget identifier, in the `sequence_refget` column.
+    pub refget: bool,
+}
+
+impl SequenceDigests {
+    /// Names of the digests accepted by [`enable`](#method.enable).
+    pub const NAMES: &'static [&'static str] = &["md5", "sha256", "refget"];
+
+    /// Enable the digest with name `name`, returning `false` if it is not known.
+    pub fn enable(&mut self, name: &str) -> bool {
+        match name {
+            "md5" => self.md5 = true,
+            "sha256" => self.sha256 = true,
+            "refget" => self.refget = true,
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// MD5 digest of `sequence`, formatted as 32 lower-case hexadecimal digits.
+pub fn md5_hex(sequence: &[u8]) -> String {
+    hex(&Md5::digest(sequence))
+}
+
+/// SHA-256 digest of `sequence`, formatted as 64 lower-case hexadecimal digits.
+pub fn sha256_hex(sequence: &[u8]) -> String {
+    hex(&Sha256::digest(sequence))
+}
+
+/// GA4GH refget identifier of `sequence`: `SQ.` followed by the first 24 bytes of its
+/// SHA-512 digest, encoded as URL-safe base64 without padding.
+pub fn refget(sequence: &[u8]) -> String {
+    let digest = Sha512::digest(sequence);
+    format!("SQ.{}", URL_SAFE_NO_PAD.encode(&digest[..24]))
+}
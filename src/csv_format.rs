@@ -0,0 +1,221 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use chrono::NaiveDate;
+
+use schema::Column;
+
+/// When fields should be enclosed in quotes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteStyle {
+    /// Quote every field.
+    Always,
+    /// Quote every field that is not a number.
+    NonNumeric,
+    /// Quote only fields that contain special characters or would otherwise be read as NULL.
+    Necessary,
+    /// Never quote fields, escaping special characters instead (requires `Escape::Backslash`).
+    Never,
+}
+
+/// How special characters are escaped inside a field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Escape {
+    /// Quote characters are doubled, as specified by RFC 4180.
+    Double,
+    /// Quote characters, backslashes, tabs and line breaks are escaped with a backslash,
+    /// as in the PostgreSQL `text` format.
+    Backslash,
+}
+
+/// Dialect of the CSV / TSV output files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvFormat {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub quote_style: QuoteStyle,
+    pub escape: Escape,
+    /// String used to represent missing values.
+    pub null: String,
+    pub line_terminator: String,
+    /// Whether to write a header row with the column names.
+    pub header: bool,
+}
+
+impl Default for CsvFormat {
+    /// Tab-separated values which can be loaded using PostgreSQL's `COPY ... csv`.
+    fn default() -> CsvFormat {
+        CsvFormat {
+            delimiter: b'\t',
+            quote: b'"',
+            quote_style: QuoteStyle::NonNumeric,
+            escape: Escape::Double,
+            null: String::new(),
+            line_terminator: String::from("\n"),
+            header: false,
+        }
+    }
+}
+
+impl CsvFormat {
+    /// Comma-separated values as specified by RFC 4180.
+    pub fn rfc4180() -> CsvFormat {
+        CsvFormat {
+            delimiter: b',',
+            quote_style: QuoteStyle::Necessary,
+            line_terminator: String::from("\r\n"),
+            ..Default::default()
+        }
+    }
+
+    /// Check that the format can represent any value, i.e. that fields which contain the
+    /// delimiter or line breaks are either quoted or escaped.
+    pub fn validate(&self) -> io::Result<()> {
+        let message = if self.delimiter == self.quote {
+            "the delimiter and the quote character must be different"
+        } else if self.quote_style == QuoteStyle::Never && self.escape != Escape::Backslash {
+            "fields which are never quoted have to be escaped with a backslash"
+        } else {
+            return Ok(());
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidInput, message))
+    }
+
+    /// Start writing a new record to `output`.
+    pub fn record<'a, W: Write>(&'a self, output: &'a mut W) -> CsvRecord<'a, W> {
+        CsvRecord {
+            format: self,
+            output,
+            first: true,
+        }
+    }
+
+    /// Write a header row containing the names of `columns`.
+    pub fn write_header<W: Write>(&self, output: &mut W, columns: &[Column]) -> io::Result<()> {
+        let mut record = self.record(output);
+        for column in columns {
+            record.string(column.name)?;
+        }
+        record.finish()
+    }
+
+    fn needs_quotes(&self, value: &str) -> bool {
+        (value.is_empty() && self.null.is_empty())
+            || value == self.null
+            || value
+                .bytes()
+                .any(|b| b == self.delimiter || b == self.quote || b == b'\n' || b == b'\r')
+    }
+
+    fn write_escaped<W: Write>(&self, output: &mut W, value: &str, quoted: bool) -> io::Result<()> {
+        let mut start = 0;
+        for (i, b) in value.bytes().enumerate() {
+            let escaped: &[u8] = match self.escape {
+                Escape::Double if quoted && b == self.quote => &[self.quote, self.quote],
+                Escape::Double => continue,
+                Escape::Backslash => match b {
+                    b'\\' => b"\\\\",
+                    b'\t' => b"\\t",
+                    b'\n' => b"\\n",
+                    b'\r' => b"\\r",
+                    _ if (quoted && b == self.quote) || (!quoted && b == self.delimiter) => {
+                        output.write_all(&value.as_bytes()[start..i])?;
+                        output.write_all(&[b'\\', b])?;
+                        start = i + 1;
+                        continue;
+                    }
+                    _ => continue,
+                },
+            };
+            output.write_all(&value.as_bytes()[start..i])?;
+            output.write_all(escaped)?;
+            start = i + 1;
+        }
+        output.write_all(&value.as_bytes()[start..])
+    }
+}
+
+/// A single record (line) of a CSV file which is being written.
+pub struct CsvRecord<'a, W: Write + 'a> {
+    format: &'a CsvFormat,
+    output: &'a mut W,
+    first: bool,
+}
+
+impl<'a, W: Write> CsvRecord<'a, W> {
+    fn write_delimiter(&mut self) -> io::Result<()> {
+        if !self.first {
+            self.output.write_all(&[self.format.delimiter])?;
+        }
+        self.first = false;
+        Ok(())
+    }
+
+    /// Write a text field.
+    pub fn string(&mut self, value: &str) -> io::Result<()> {
+        self.write_delimiter()?;
+        let format = self.format;
+        let quoted = match format.quote_style {
+            QuoteStyle::Always | QuoteStyle::NonNumeric => true,
+            QuoteStyle::Necessary => format.needs_quotes(value),
+            QuoteStyle::Never => false,
+        };
+        if quoted {
+            self.output.write_all(&[format.quote])?;
+            format.write_escaped(self.output, value, true)?;
+            self.output.write_all(&[format.quote])
+        } else {
+            format.write_escaped(self.output, value, false)
+        }
+    }
+
+    /// Write a numeric field.
+    pub fn number<N: Display>(&mut self, value: N) -> io::Result<()> {
+        self.write_delimiter()?;
+        let format = self.format;
+        if format.quote_style == QuoteStyle::Always {
+            let quote = format.quote as char;
+            write!(self.output, "{}{}{}", quote, value, quote)
+        } else {
+            write!(self.output, "{}", value)
+        }
+    }
+
+    /// Write a boolean field, as `true` or `false`.
+    pub fn boolean(&mut self, value: bool) -> io::Result<()> {
+        self.number(value)
+    }
+
+    /// Write a date field, in the ISO-8601 `YYYY-MM-DD` format.
+    pub fn date(&mut self, value: NaiveDate) -> io::Result<()> {
+        self.number(value)
+    }
+
+    /// Write a numeric field, or a missing value if there is none.
+    pub fn optional_number<N: Display>(&mut self, value: Option<N>) -> io::Result<()> {
+        match value {
+            Some(value) => self.number(value),
+            None => self.null(),
+        }
+    }
+
+    /// Write a string, or a missing value if there is none.
+    pub fn optional_string(&mut self, value: Option<&str>) -> io::Result<()> {
+        match value {
+            Some(value) => self.string(value),
+            None => self.null(),
+        }
+    }
+
+    /// Write a missing value.
+    pub fn null(&mut self) -> io::Result<()> {
+        self.write_delimiter()?;
+        self.output.write_all(self.format.null.as_bytes())
+    }
+
+    /// Terminate the record.
+    pub fn finish(self) -> io::Result<()> {
+        self.output
+            .write_all(self.format.line_terminator.as_bytes())
+    }
+}
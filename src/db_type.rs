@@ -0,0 +1,297 @@
+use std::fmt;
+
+/// Kind of resource that a cross-referenced database describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DbCategory {
+    /// UniProt Knowledgebase entries and isoforms.
+    UniProt,
+    /// Nucleotide and protein sequence archives, e.g. EMBL or RefSeq.
+    Sequence,
+    /// Genome annotation resources, e.g. Ensembl.
+    Genome,
+    /// Model organism databases, e.g. FlyBase or SGD.
+    ModelOrganism,
+    /// Macromolecular structures.
+    Structure,
+    /// Sequences from patent applications.
+    Patent,
+    /// Databases which are not known to the parser.
+    Other,
+}
+
+impl DbCategory {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DbCategory::UniProt => "uniprot",
+            DbCategory::Sequence => "sequence",
+            DbCategory::Genome => "genome",
+            DbCategory::ModelOrganism => "model_organism",
+            DbCategory::Structure => "structure",
+            DbCategory::Patent => "patent",
+            DbCategory::Other => "other",
+        }
+    }
+}
+
+/// Database referenced by the `type` attribute of a `<dbReference>` element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DbType {
+    SwissProt,
+    SwissProtIsoforms,
+    TrEmbl,
+    TrEmblIsoforms,
+    Embl,
+    EmblCon,
+    EmblTpa,
+    EmblWgs,
+    Ensembl,
+    EnsemblBacteria,
+    EnsemblFungi,
+    EnsemblMetazoa,
+    EnsemblPlants,
+    EnsemblProtists,
+    Epo,
+    FlyBase,
+    HInvDb,
+    Ipi,
+    Jpo,
+    Kipo,
+    Patric,
+    Pdb,
+    Pir,
+    PirArc,
+    Prf,
+    RefSeq,
+    Seed,
+    Sgd,
+    Tair,
+    Trome,
+    Uspto,
+    VectorBase,
+    Vega,
+    WormBase,
+    /// Database which is not known to the parser, with its name as given in the XML.
+    Other(String),
+}
+
+impl DbType {
+    /// All databases known to the parser.
+    pub const KNOWN: &'static [DbType] = &[
+        DbType::SwissProt,
+        DbType::SwissProtIsoforms,
+        DbType::TrEmbl,
+        DbType::TrEmblIsoforms,
+        DbType::Embl,
+        DbType::EmblCon,
+        DbType::EmblTpa,
+        DbType::EmblWgs,
+        DbType::Ensembl,
+        DbType::EnsemblBacteria,
+        DbType::EnsemblFungi,
+        DbType::EnsemblMetazoa,
+        DbType::EnsemblPlants,
+        DbType::EnsemblProtists,
+        DbType::Epo,
+        DbType::FlyBase,
+        DbType::HInvDb,
+        DbType::Ipi,
+        DbType::Jpo,
+        DbType::Kipo,
+        DbType::Patric,
+        DbType::Pdb,
+        DbType::Pir,
+        DbType::PirArc,
+        DbType::Prf,
+        DbType::RefSeq,
+        DbType::Seed,
+        DbType::Sgd,
+        DbType::Tair,
+        DbType::Trome,
+        DbType::Uspto,
+        DbType::VectorBase,
+        DbType::Vega,
+        DbType::WormBase,
+    ];
+
+    /// Database with the canonical name `name`, as used in the UniParc XML.
+    pub fn from_name(name: &str) -> DbType {
+        match name {
+            "UniProtKB/Swiss-Prot" => DbType::SwissProt,
+            "UniProtKB/Swiss-Prot protein isoforms" => DbType::SwissProtIsoforms,
+            "UniProtKB/TrEMBL" => DbType::TrEmbl,
+            "UniProtKB/TrEMBL protein isoforms" => DbType::TrEmblIsoforms,
+            "EMBL" => DbType::Embl,
+            "EMBL_CON" => DbType::EmblCon,
+            "EMBL_TPA" => DbType::EmblTpa,
+            "EMBLWGS" => DbType::EmblWgs,
+            "Ensembl" => DbType::Ensembl,
+            "EnsemblBacteria" => DbType::EnsemblBacteria,
+            "EnsemblFungi" => DbType::EnsemblFungi,
+            "EnsemblMetazoa" => DbType::EnsemblMetazoa,
+            "EnsemblPlants" => DbType::EnsemblPlants,
+            "EnsemblProtists" => DbType::EnsemblProtists,
+            "EPO" => DbType::Epo,
+            "FlyBase" => DbType::FlyBase,
+            "H-InvDB" => DbType::HInvDb,
+            "IPI" => DbType::Ipi,
+            "JPO" => DbType::Jpo,
+            "KIPO" => DbType::Kipo,
+            "PATRIC" => DbType::Patric,
+            "PDB" => DbType::Pdb,
+            "PIR" => DbType::Pir,
+            "PIRARC" => DbType::PirArc,
+            "PRF" => DbType::Prf,
+            "RefSeq" => DbType::RefSeq,
+            "SEED" => DbType::Seed,
+            "SGD" => DbType::Sgd,
+            "TAIR_ARABIDOPSIS" => DbType::Tair,
+            "TROME" => DbType::Trome,
+            "USPTO" => DbType::Uspto,
+            "VectorBase" => DbType::VectorBase,
+            "VEGA" => DbType::Vega,
+            "WormBase" => DbType::WormBase,
+            _ => DbType::Other(name.to_string()),
+        }
+    }
+
+    /// Canonical name of the database, category, and template of the URL of an ID.
+    fn info(&self) -> (&str, DbCategory, Option<&'static str>) {
+        use self::DbCategory::*;
+
+        match *self {
+            DbType::SwissProt => (
+                "UniProtKB/Swiss-Prot",
+                UniProt,
+                Some("https://www.uniprot.org/uniprotkb/{id}"),
+            ),
+            DbType::SwissProtIsoforms => (
+                "UniProtKB/Swiss-Prot protein isoforms",
+                UniProt,
+                Some("https://www.uniprot.org/uniprotkb/{id}"),
+            ),
+            DbType::TrEmbl => (
+                "UniProtKB/TrEMBL",
+                UniProt,
+                Some("https://www.uniprot.org/uniprotkb/{id}"),
+            ),
+            DbType::TrEmblIsoforms => (
+                "UniProtKB/TrEMBL protein isoforms",
+                UniProt,
+                Some("https://www.uniprot.org/uniprotkb/{id}"),
+            ),
+            DbType::Embl => (
+                "EMBL",
+                Sequence,
+                Some("https://www.ebi.ac.uk/ena/browser/view/{id}"),
+            ),
+            DbType::EmblCon => (
+                "EMBL_CON",
+                Sequence,
+                Some("https://www.ebi.ac.uk/ena/browser/view/{id}"),
+            ),
+            DbType::EmblTpa => (
+                "EMBL_TPA",
+                Sequence,
+                Some("https://www.ebi.ac.uk/ena/browser/view/{id}"),
+            ),
+            DbType::EmblWgs => (
+                "EMBLWGS",
+                Sequence,
+                Some("https://www.ebi.ac.uk/ena/browser/view/{id}"),
+            ),
+            DbType::Ensembl => ("Ensembl", Genome, Some("https://www.ensembl.org/id/{id}")),
+            DbType::EnsemblBacteria => (
+                "EnsemblBacteria",
+                Genome,
+                Some("https://bacteria.ensembl.org/id/{id}"),
+            ),
+            DbType::EnsemblFungi => (
+                "EnsemblFungi",
+                Genome,
+                Some("https://fungi.ensembl.org/id/{id}"),
+            ),
+            DbType::EnsemblMetazoa => (
+                "EnsemblMetazoa",
+                Genome,
+                Some("https://metazoa.ensembl.org/id/{id}"),
+            ),
+            DbType::EnsemblPlants => (
+                "EnsemblPlants",
+                Genome,
+                Some("https://plants.ensembl.org/id/{id}"),
+            ),
+            DbType::EnsemblProtists => (
+                "EnsemblProtists",
+                Genome,
+                Some("https://protists.ensembl.org/id/{id}"),
+            ),
+            DbType::Epo => ("EPO", Patent, None),
+            DbType::FlyBase => (
+                "FlyBase",
+                ModelOrganism,
+                Some("https://flybase.org/reports/{id}"),
+            ),
+            DbType::HInvDb => ("H-InvDB", Sequence, None),
+            DbType::Ipi => ("IPI", Sequence, None),
+            DbType::Jpo => ("JPO", Patent, None),
+            DbType::Kipo => ("KIPO", Patent, None),
+            DbType::Patric => ("PATRIC", Genome, None),
+            DbType::Pdb => (
+                "PDB",
+                Structure,
+                Some("https://www.rcsb.org/structure/{id}"),
+            ),
+            DbType::Pir => ("PIR", Sequence, None),
+            DbType::PirArc => ("PIRARC", Sequence, None),
+            DbType::Prf => ("PRF", Sequence, None),
+            DbType::RefSeq => (
+                "RefSeq",
+                Sequence,
+                Some("https://www.ncbi.nlm.nih.gov/protein/{id}"),
+            ),
+            DbType::Seed => ("SEED", Genome, None),
+            DbType::Sgd => (
+                "SGD",
+                ModelOrganism,
+                Some("https://www.yeastgenome.org/locus/{id}"),
+            ),
+            DbType::Tair => ("TAIR_ARABIDOPSIS", ModelOrganism, None),
+            DbType::Trome => ("TROME", Sequence, None),
+            DbType::Uspto => ("USPTO", Patent, None),
+            DbType::VectorBase => ("VectorBase", ModelOrganism, None),
+            DbType::Vega => ("VEGA", Genome, None),
+            DbType::WormBase => (
+                "WormBase",
+                ModelOrganism,
+                Some("https://wormbase.org/search/protein/{id}"),
+            ),
+            DbType::Other(ref name) => (name, Other, None),
+        }
+    }
+
+    /// Name of the database, as used in the UniParc XML.
+    pub fn name(&self) -> &str {
+        self.info().0
+    }
+
+    pub fn category(&self) -> DbCategory {
+        self.info().1
+    }
+
+    /// Template of the URL of an entry in the database, with `{id}` in place of its ID.
+    pub fn url_template(&self) -> Option<&'static str> {
+        self.info().2
+    }
+
+    /// URL of the entry with ID `id` in the database, if it is known.
+    pub fn url(&self, id: &str) -> Option<String> {
+        self.url_template()
+            .map(|template| template.replace("{id}", id))
+    }
+}
+
+impl fmt::Display for DbType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
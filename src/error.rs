@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use quick_xml::errors::Error as XmlError;
+
+/// Error encountered while parsing the UniParc XML stream.
+#[derive(Debug)]
+pub struct ParseError {
+    /// Position in the input stream at which the error was detected, in bytes.
+    pub position: usize,
+    /// Accession of the UniParc entry being parsed (empty if not yet known).
+    pub uniparc_id: String,
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+}
+
+/// The different kinds of errors that can be encountered while parsing.
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    /// Reading from the input stream failed.
+    Io(io::Error),
+    /// The underlying XML reader failed.
+    Xml(XmlError),
+    /// An attribute value could not be decoded or converted to the expected type.
+    InvalidValue {
+        element: String,
+        attribute: String,
+        value: String,
+    },
+    /// An element has an attribute that the parser does not know how to handle.
+    UnknownAttribute { element: String, attribute: String },
+    /// An element is missing a required attribute.
+    MissingAttribute { element: String, attribute: String },
+    /// An element appeared in a place where it was not expected.
+    UnexpectedElement { element: String },
+    /// The length of a sequence does not match its `length` attribute.
+    SequenceLength { expected: u32, actual: u32 },
+    /// The CRC64 checksum of a sequence does not match its `checksum` attribute.
+    SequenceChecksum { expected: String, actual: String },
+    /// The input ended in the middle of an entry.
+    UnexpectedEof,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseErrorKind::Io(ref error) => write!(f, "I/O error: {}", error),
+            ParseErrorKind::Xml(ref error) => write!(f, "XML error: {}", error),
+            ParseErrorKind::InvalidValue {
+                ref element,
+                ref attribute,
+                ref value,
+            } => write!(
+                f,
+                "invalid value '{}' for attribute '{}' of element '{}'",
+                value, attribute, element
+            ),
+            ParseErrorKind::UnknownAttribute {
+                ref element,
+                ref attribute,
+            } => write!(
+                f,
+                "unknown attribute '{}' for element '{}'",
+                attribute, element
+            ),
+            ParseErrorKind::MissingAttribute {
+                ref element,
+                ref attribute,
+            } => write!(
+                f,
+                "missing attribute '{}' for element '{}'",
+                attribute, element
+            ),
+            ParseErrorKind::UnexpectedElement { ref element } => {
+                write!(f, "unexpected element '{}'", element)
+            }
+            ParseErrorKind::SequenceLength { expected, actual } => write!(
+                f,
+                "sequence has length {} but its length attribute is {}",
+                actual, expected
+            ),
+            ParseErrorKind::SequenceChecksum {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "sequence has checksum {} but its checksum attribute is {}",
+                actual, expected
+            ),
+            ParseErrorKind::UnexpectedEof => write!(f, "unexpected end of file"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Error at position {} (UniParc ID '{}'): {}",
+            self.position, self.uniparc_id, self.kind
+        )
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use md5::{Digest, Md5};
+
+use model::{Uniparc, UniparcDomain, UniparcEntryRef, UniparcProperty, UniparcXRef};
+use reader::ParseOptions;
+use sink::Sink;
+
+/// Field of a UniParc entry which can be used in a FASTA header.
+#[derive(Debug, Clone, PartialEq)]
+enum HeaderField {
+    UniparcId,
+    SequenceLength,
+    SequenceChecksum,
+    SequenceMd5,
+    SequenceSha256,
+    SequenceRefget,
+    /// Values of the properties with the given name, e.g. `protein_name`.
+    Property(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Field(HeaderField),
+}
+
+/// Template of the header line of each FASTA record.
+///
+/// Fields are given in braces and replaced by the values of the entry, e.g.
+/// `{uniparc_id} {protein_name} OX={ncbi_taxonomy_id}`. Columns of the `uniparc` table
+/// other than `sequence` can be used, including the digests which are enabled, as well as
+/// the names of built-in properties, such as `protein_name`, `ncbi_taxonomy_id` or
+/// `uniprot_kb_accession`, and of property types mapped to a table. Distinct values of a
+/// property are joined with `;`, and missing values are left empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FastaHeader {
+    segments: Vec<Segment>,
+}
+
+impl FastaHeader {
+    /// Parse `template`, failing on fields which are not filled when parsing with `options`.
+    pub fn parse(template: &str, options: &ParseOptions) -> Result<FastaHeader, Box<dyn Error>> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => return Err(From::from(format!("unterminated field in '{}'", template))),
+            };
+            segments.push(Segment::Field(header_field(
+                &rest[start + 1..end],
+                options,
+            )?));
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(FastaHeader { segments })
+    }
+
+    /// Whether the template uses the values of properties with name `property`.
+    fn uses_property(&self, property: &str) -> bool {
+        self.segments.iter().any(|segment| match *segment {
+            Segment::Field(HeaderField::Property(ref name)) => name == property,
+            _ => false,
+        })
+    }
+
+    fn render(&self, uniparc: &Uniparc, properties: &HashMap<String, Vec<String>>) -> String {
+        let mut header = String::new();
+        for segment in &self.segments {
+            let field = match *segment {
+                Segment::Text(ref text) => {
+                    header.push_str(text);
+                    continue;
+                }
+                Segment::Field(ref field) => field,
+            };
+            match *field {
+                HeaderField::UniparcId => header.push_str(&uniparc.uniparc_id),
+                HeaderField::SequenceLength => {
+                    header.push_str(&uniparc.sequence_length.to_string())
+                }
+                HeaderField::SequenceChecksum => header.push_str(&uniparc.sequence_checksum),
+                HeaderField::SequenceMd5 => {
+                    header.push_str(uniparc.sequence_md5.as_deref().unwrap_or(""))
+                }
+                HeaderField::SequenceSha256 => {
+                    header.push_str(uniparc.sequence_sha256.as_deref().unwrap_or(""))
+                }
+                HeaderField::SequenceRefget => {
+                    header.push_str(uniparc.sequence_refget.as_deref().unwrap_or(""))
+                }
+                HeaderField::Property(ref name) => {
+                    if let Some(values) = properties.get(name) {
+                        header.push_str(&values.join(";"));
+                    }
+                }
+            }
+        }
+        header
+    }
+}
+
+impl Default for FastaHeader {
+    fn default() -> FastaHeader {
+        FastaHeader {
+            segments: vec![Segment::Field(HeaderField::UniparcId)],
+        }
+    }
+}
+
+fn header_field(name: &str, options: &ParseOptions) -> Result<HeaderField, Box<dyn Error>> {
+    let digests = options.digests;
+    let field = match name {
+        "uniparc_id" => HeaderField::UniparcId,
+        "sequence_length" => HeaderField::SequenceLength,
+        "sequence_checksum" => HeaderField::SequenceChecksum,
+        "sequence_md5" if digests.md5 => HeaderField::SequenceMd5,
+        "sequence_sha256" if digests.sha256 => HeaderField::SequenceSha256,
+        "sequence_refget" if digests.refget => HeaderField::SequenceRefget,
+        "sequence_md5" | "sequence_sha256" | "sequence_refget" => {
+            return Err(From::from(format!(
+                "field '{{{}}}' needs the digest to be enabled",
+                name
+            )))
+        }
+        "" => return Err(From::from("empty field")),
+        _ if options.properties.is_known_property(name) => HeaderField::Property(name.to_string()),
+        _ => return Err(From::from(format!("unknown field '{{{}}}'", name))),
+    };
+    Ok(field)
+}
+
+/// Write a single FASTA record, wrapping the sequence after `line_width` residues.
+fn write_record<W: Write>(
+    output: &mut W,
+    header: &str,
+    sequence: &[u8],
+    line_width: usize,
+) -> io::Result<()> {
+    writeln!(output, ">{}", header)?;
+    if line_width == 0 {
+        output.write_all(sequence)?;
+        output.write_all(b"\n")?;
+    } else {
+        for line in sequence.chunks(line_width) {
+            output.write_all(line)?;
+            output.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// Options for writing FASTA files.
+#[derive(Debug, Clone)]
+pub struct FastaOptions {
+    pub header: FastaHeader,
+    /// Maximum number of residues on each sequence line, or 0 to not wrap sequences.
+    pub line_width: usize,
+}
+
+impl Default for FastaOptions {
+    fn default() -> FastaOptions {
+        FastaOptions {
+            header: FastaHeader::default(),
+            line_width: 60,
+        }
+    }
+}
+
+/// Sink which writes the sequence of each entry as a FASTA record.
+///
+/// Each record is written once all properties of its entry have been seen, i.e. when the
+/// next entry starts or the sink is finished.
+pub struct FastaSink<W: Write> {
+    output: W,
+    options: FastaOptions,
+    uniparc: Option<Uniparc>,
+    properties: HashMap<String, Vec<String>>,
+}
+
+/// Initialize the `uniparc.fasta` output file
+pub fn initialize_outputs_fasta(
+    basedir: &Path,
+    options: &FastaOptions,
+) -> Result<FastaSink<BufWriter<File>>, Box<dyn Error>> {
+    let file = File::create(basedir.join("uniparc.fasta"))?;
+    Ok(FastaSink::new(BufWriter::new(file), options.clone()))
+}
+
+impl<W: Write> FastaSink<W> {
+    pub fn new(output: W, options: FastaOptions) -> FastaSink<W> {
+        FastaSink {
+            output,
+            options,
+            uniparc: None,
+            properties: HashMap::new(),
+        }
+    }
+
+    /// Consume the sink, returning the output. Call `finish` first to write the last record.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    fn add_property(&mut self, property: &str, value: &str) {
+        if !self.options.header.uses_property(property) {
+            return;
+        }
+        let values = self.properties.entry(property.to_string()).or_default();
+        if !values.iter().any(|v| v == value) {
+            values.push(value.to_string());
+        }
+    }
+
+    /// Write the record of the current entry, if any.
+    fn write_record(&mut self) -> Result<(), Box<dyn Error>> {
+        let uniparc = match self.uniparc.take() {
+            Some(uniparc) => uniparc,
+            None => return Ok(()),
+        };
+        let header = self.options.header.render(&uniparc, &self.properties);
+        self.properties.clear();
+        write_record(
+            &mut self.output,
+            &header,
+            uniparc.sequence.as_bytes(),
+            self.options.line_width,
+        )?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Sink for FastaSink<W> {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        self.write_record()?;
+        self.uniparc = Some(uniparc.clone());
+        Ok(())
+    }
+
+    fn on_xref(&mut self, _xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        self.add_property(&property.property, &property.value);
+        Ok(())
+    }
+
+    fn on_domain(&mut self, _domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.write_record()?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        // Only the sequence and properties are needed, so the rest of the entry is not copied.
+        self.write_record()?;
+        self.uniparc = Some(entry.uniparc.clone().into_owned());
+        for &(_, properties) in entry.properties.named().iter() {
+            for property in properties {
+                self.add_property(&property.property, &property.value);
+            }
+        }
+        for property in &entry.extra_properties {
+            self.add_property(&property.property, &property.value);
+        }
+        Ok(())
+    }
+}
+
+/// Options for writing the sequences of domains to FASTA files.
+#[derive(Debug, Clone)]
+pub struct DomainFastaOptions {
+    /// Databases whose domains are written, e.g. `Pfam` or `Gene3D`, or empty for all.
+    ///
+    /// Database names are compared ignoring ASCII case.
+    pub databases: Vec<String>,
+    /// Write each distinct domain sequence only once, with the header of its first domain.
+    ///
+    /// The MD5 digest of every distinct domain sequence is kept in memory until the sink is
+    /// dropped, which takes roughly 20 to 40 bytes per sequence, e.g. 1 to 2 GB for 50 million
+    /// distinct domain sequences.
+    pub deduplicate: bool,
+    /// Maximum number of residues on each sequence line, or 0 to not wrap sequences.
+    pub line_width: usize,
+}
+
+impl Default for DomainFastaOptions {
+    fn default() -> DomainFastaOptions {
+        DomainFastaOptions {
+            databases: Vec::new(),
+            deduplicate: false,
+            line_width: 60,
+        }
+    }
+}
+
+/// Sink which writes the part of the sequence covered by each domain as a FASTA record.
+///
+/// Headers have the form `>{uniparc_id}/{domain_start}-{domain_end} {database}:{database_id}`,
+/// followed by the InterPro ID of the domain, if it has one. Domains which are not within
+/// their sequence are skipped, and counted in a summary printed by `finish`.
+pub struct DomainFastaSink<W: Write> {
+    output: W,
+    options: DomainFastaOptions,
+    /// Sequence of the current entry.
+    sequence: String,
+    /// MD5 digests of the domain sequences written so far, if they are deduplicated.
+    seen: HashSet<[u8; 16]>,
+    /// Number of domains which were skipped because they are not within their sequence.
+    skipped: u64,
+}
+
+/// Initialize the `domain.fasta` output file
+pub fn initialize_outputs_domain_fasta(
+    basedir: &Path,
+    options: &DomainFastaOptions,
+) -> Result<DomainFastaSink<BufWriter<File>>, Box<dyn Error>> {
+    let file = File::create(basedir.join("domain.fasta"))?;
+    Ok(DomainFastaSink::new(BufWriter::new(file), options.clone()))
+}
+
+impl<W: Write> DomainFastaSink<W> {
+    pub fn new(output: W, options: DomainFastaOptions) -> DomainFastaSink<W> {
+        DomainFastaSink {
+            output,
+            options,
+            sequence: String::new(),
+            seen: HashSet::new(),
+            skipped: 0,
+        }
+    }
+
+    /// Number of domains which were skipped so far because they are not within their sequence.
+    pub fn skipped_domains(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Consume the sink, returning the output.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    fn write_domain(
+        &mut self,
+        uniparc_id: &str,
+        database: &str,
+        database_id: &str,
+        interpro_id: &str,
+        domain_start: u32,
+        domain_end: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.options.databases.is_empty()
+            && !self
+                .options
+                .databases
+                .iter()
+                .any(|d| d.eq_ignore_ascii_case(database))
+        {
+            return Ok(());
+        }
+        // Domain coordinates are 1-based and inclusive.
+        let (start, end) = (domain_start as usize, domain_end as usize);
+        if start == 0 || end < start || end > self.sequence.len() {
+            self.skipped += 1;
+            return Ok(());
+        }
+        let sequence = &self.sequence.as_bytes()[start - 1..end];
+        if self.options.deduplicate && !self.seen.insert(Md5::digest(sequence).into()) {
+            return Ok(());
+        }
+        let mut header = format!(
+            "{}/{}-{} {}:{}",
+            uniparc_id, domain_start, domain_end, database, database_id
+        );
+        if !interpro_id.is_empty() {
+            header.push(' ');
+            header.push_str(interpro_id);
+        }
+        write_record(&mut self.output, &header, sequence, self.options.line_width)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Sink for DomainFastaSink<W> {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        self.sequence.clear();
+        self.sequence.push_str(&uniparc.sequence);
+        Ok(())
+    }
+
+    fn on_xref(&mut self, _xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_property(&mut self, _property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        self.write_domain(
+            &domain.uniparc_id,
+            &domain.database,
+            &domain.database_id,
+            &domain.interpro_id,
+            domain.domain_start,
+            domain.domain_end,
+        )
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.skipped > 0 {
+            println!(
+                "Skipped {} domains which are not within their sequence.",
+                self.skipped
+            );
+        }
+        self.output.flush()?;
+        Ok(())
+    }
+
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        self.sequence.clear();
+        self.sequence.push_str(&entry.uniparc.sequence);
+        for domain in &entry.domains {
+            self.write_domain(
+                &domain.uniparc_id,
+                &domain.database,
+                &domain.database_id,
+                &domain.interpro_id,
+                domain.domain_start,
+                domain.domain_end,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::BufRead;
+
+use db_type::DbType;
+use model::{UniparcEntryRef, UniparcPropertyRef};
+use reader::ParseOptions;
+
+/// Name of the property which gives the NCBI taxonomy ID of a cross-reference.
+const TAXONOMY_PROPERTY: &str = "ncbi_taxonomy_id";
+
+/// Part of an entry which a filter is applied to.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FilterScope {
+    /// Keep whole entries if any of their cross-references matches.
+    #[default]
+    Entries,
+    /// Keep only the matching cross-references (and their properties) of each entry, and
+    /// drop entries without any matching cross-references.
+    XRefs,
+}
+
+/// Filter on the `NCBI_taxonomy_id` properties of cross-references.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxonFilter {
+    taxa: HashSet<u32>,
+    pub scope: FilterScope,
+}
+
+impl TaxonFilter {
+    pub fn new<I: IntoIterator<Item = u32>>(taxa: I, scope: FilterScope) -> TaxonFilter {
+        TaxonFilter {
+            taxa: taxa.into_iter().collect(),
+            scope,
+        }
+    }
+
+    pub fn contains(&self, taxon: u32) -> bool {
+        self.taxa.contains(&taxon)
+    }
+
+    /// Whether the value of an `NCBI_taxonomy_id` property is one of the taxa.
+    fn matches(&self, value: &str) -> bool {
+        value.trim().parse().is_ok_and(|taxon| self.contains(taxon))
+    }
+}
+
+/// Filter on the databases of the cross-references of each entry.
+///
+/// Databases are compared by their [`DbType`](enum.DbType.html); empty lists do not filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DbFilter {
+    /// Only keep entries with a cross-reference to at least one of these databases.
+    pub require: Vec<DbType>,
+    /// Drop entries with a cross-reference to any of these databases.
+    pub exclude: Vec<DbType>,
+    /// Only keep cross-references (and their properties) to these databases, while still
+    /// keeping entries without any such cross-references.
+    pub xrefs: Vec<DbType>,
+}
+
+impl DbFilter {
+    fn is_empty(&self) -> bool {
+        self.require.is_empty() && self.exclude.is_empty() && self.xrefs.is_empty()
+    }
+}
+
+/// Filter on the domains (`signatureSequenceMatch` elements) of each entry.
+///
+/// Entries are kept if they have a domain from one of the databases or with one of the
+/// InterPro IDs. Both are compared ignoring ASCII case, so `pfam` matches `Pfam`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DomainFilter {
+    /// Member databases of the domains, e.g. `Pfam` or `Gene3D`.
+    pub databases: Vec<String>,
+    /// InterPro IDs of the domains, e.g. `IPR000971`.
+    pub interpro_ids: Vec<String>,
+}
+
+impl DomainFilter {
+    fn is_empty(&self) -> bool {
+        self.databases.is_empty() && self.interpro_ids.is_empty()
+    }
+}
+
+/// Read NCBI taxonomy IDs, separated by whitespace, skipping lines starting with `#`.
+pub fn read_taxa<R: BufRead>(input: R) -> Result<Vec<u32>, Box<dyn Error>> {
+    let mut taxa = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.starts_with('#') {
+            continue;
+        }
+        for value in line.split_whitespace() {
+            match value.parse() {
+                Ok(taxon) => taxa.push(taxon),
+                Err(_) => return Err(From::from(format!("invalid taxonomy ID '{}'", value))),
+            }
+        }
+    }
+    Ok(taxa)
+}
+
+/// All properties of `entry`, regardless of the table to which they are written.
+fn properties<'e, 'a>(
+    entry: &'e UniparcEntryRef<'a>,
+) -> impl Iterator<Item = &'e UniparcPropertyRef<'a>> {
+    IntoIterator::into_iter(entry.properties.named())
+        .flat_map(|(_, properties)| properties.iter())
+        .chain(entry.extra_properties.iter())
+}
+
+/// Keep only the cross-references of `entry` (and their properties) with IDs in `xref_ids`.
+fn retain_xrefs(entry: &mut UniparcEntryRef, xref_ids: &HashSet<u64>) {
+    entry.xrefs.retain(|xref| xref_ids.contains(&xref.xref_id));
+    for (_, properties) in entry.properties.named_mut() {
+        properties.retain(|property| xref_ids.contains(&property.xref_id));
+    }
+    entry
+        .extra_properties
+        .retain(|property| xref_ids.contains(&property.xref_id));
+}
+
+/// IDs of the cross-references of `entry` with a taxon in `filter`.
+fn taxon_xrefs(entry: &UniparcEntryRef, filter: &TaxonFilter) -> HashSet<u64> {
+    properties(entry)
+        .filter(|property| property.property == TAXONOMY_PROPERTY)
+        .filter(|property| filter.matches(&property.value))
+        .map(|property| property.xref_id)
+        .collect()
+}
+
+/// Whether `entry` has a required database, if any, and no excluded database.
+fn filter_databases(entry: &UniparcEntryRef, filter: &DbFilter) -> bool {
+    if !filter.require.is_empty()
+        && !entry
+            .xrefs
+            .iter()
+            .any(|xref| filter.require.contains(&xref.db_type))
+    {
+        return false;
+    }
+    !entry
+        .xrefs
+        .iter()
+        .any(|xref| filter.exclude.contains(&xref.db_type))
+}
+
+/// IDs of the cross-references of `entry` to the databases in `filter.xrefs`.
+fn database_xrefs(entry: &UniparcEntryRef, filter: &DbFilter) -> HashSet<u64> {
+    entry
+        .xrefs
+        .iter()
+        .filter(|xref| filter.xrefs.contains(&xref.db_type))
+        .map(|xref| xref.xref_id)
+        .collect()
+}
+
+fn filter_domains(entry: &UniparcEntryRef, filter: &DomainFilter) -> bool {
+    entry.domains.iter().any(|domain| {
+        filter
+            .databases
+            .iter()
+            .any(|d| d.eq_ignore_ascii_case(&domain.database))
+            || filter
+                .interpro_ids
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(&domain.interpro_id))
+    })
+}
+
+/// Apply the filters in `options` to `entry`.
+///
+/// Returns `false` if the entry should be dropped. Cross-references which do not match a filter
+/// with [`FilterScope::XRefs`](enum.FilterScope.html), or which are not to one of the databases
+/// in `DbFilter::xrefs`, are removed from the entry.
+pub fn filter_entry(entry: &mut UniparcEntryRef, options: &ParseOptions) -> bool {
+    // Entries are selected using all of their cross-references, so every filter is evaluated
+    // before any cross-references are removed.
+    if !options.domains.is_empty() && !filter_domains(entry, &options.domains) {
+        return false;
+    }
+    if !options.databases.is_empty() && !filter_databases(entry, &options.databases) {
+        return false;
+    }
+    let mut taxon_scope = None;
+    if let Some(ref filter) = options.taxa {
+        let xref_ids = taxon_xrefs(entry, filter);
+        if xref_ids.is_empty() {
+            return false;
+        }
+        if filter.scope == FilterScope::XRefs {
+            taxon_scope = Some(xref_ids);
+        }
+    }
+
+    if !options.databases.xrefs.is_empty() {
+        let xref_ids = database_xrefs(entry, &options.databases);
+        retain_xrefs(entry, &xref_ids);
+    }
+    if let Some(xref_ids) = taxon_scope {
+        retain_xrefs(entry, &xref_ids);
+    }
+    true
+}
@@ -0,0 +1,62 @@
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use xz2::read::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: &[u8] = &[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+
+/// Wrap `input` in a decoder matching its compression format.
+///
+/// The format is detected from the magic bytes at the start of the stream.
+/// Gzip, zstd, xz and bzip2 streams are supported (including multi-member / multi-frame streams),
+/// and anything else is passed through unchanged.
+pub fn decompress<R: Read + Send + 'static>(mut input: R) -> io::Result<Box<dyn Read + Send>> {
+    // Read the magic bytes, making sure we get all of them even from short reads on a pipe.
+    let mut magic = vec![0; XZ_MAGIC.len()];
+    let mut magic_len = 0;
+    while magic_len < magic.len() {
+        match input.read(&mut magic[magic_len..]) {
+            Ok(0) => break,
+            Ok(n) => magic_len += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    magic.truncate(magic_len);
+
+    let stream = Cursor::new(magic.clone()).chain(input);
+    let decoded: Box<dyn Read + Send> = if magic.starts_with(GZIP_MAGIC) {
+        Box::new(MultiGzDecoder::new(stream))
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        Box::new(ZstdDecoder::new(stream)?)
+    } else if magic.starts_with(XZ_MAGIC) {
+        Box::new(XzDecoder::new_multi_decoder(stream))
+    } else if magic.starts_with(BZIP2_MAGIC) {
+        Box::new(MultiBzDecoder::new(stream))
+    } else {
+        Box::new(stream)
+    };
+    Ok(decoded)
+}
+
+/// Open a sequence of input files.
+///
+/// Reads from `stdin` if no paths are given. Compressed inputs are decompressed transparently.
+/// The inputs are returned separately, so that an entry which is cut off at the end of one file
+/// is not continued with the start of the next one (see [`run_inputs`](fn.run_inputs.html)).
+pub fn open_inputs<P: AsRef<Path>>(paths: &[P]) -> io::Result<Vec<Box<dyn Read + Send>>> {
+    if paths.is_empty() {
+        return Ok(vec![decompress(io::stdin())?]);
+    }
+    paths
+        .iter()
+        .map(|path| decompress(File::open(path)?))
+        .collect()
+}
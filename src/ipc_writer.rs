@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use arrow_array::RecordBatch;
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{ArrowError, SchemaRef};
+
+use batch::{EntryBatcher, TableBatch};
+use model::{Uniparc, UniparcDomain, UniparcProperty, UniparcXRef};
+use schema::SchemaOptions;
+use sink::Sink;
+
+/// Flavour of the Arrow IPC format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrowIpcFormat {
+    /// Random-access file format (Feather V2), written to `<table>.arrow`.
+    File,
+    /// Streaming format, written to `<table>.arrows`.
+    Stream,
+}
+
+enum IpcWriter {
+    File(FileWriter<BufWriter<File>>),
+    Stream(StreamWriter<BufWriter<File>>),
+}
+
+impl IpcWriter {
+    fn create(
+        path: &Path,
+        schema: &SchemaRef,
+        format: ArrowIpcFormat,
+    ) -> Result<IpcWriter, Box<dyn Error>> {
+        let file = BufWriter::new(File::create(path)?);
+        let writer = match format {
+            ArrowIpcFormat::File => IpcWriter::File(FileWriter::try_new(file, schema)?),
+            ArrowIpcFormat::Stream => IpcWriter::Stream(StreamWriter::try_new(file, schema)?),
+        };
+        Ok(writer)
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), ArrowError> {
+        match *self {
+            IpcWriter::File(ref mut writer) => writer.write(batch),
+            IpcWriter::Stream(ref mut writer) => writer.write(batch),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), ArrowError> {
+        match *self {
+            IpcWriter::File(ref mut writer) => writer.finish(),
+            IpcWriter::Stream(ref mut writer) => writer.finish(),
+        }
+    }
+}
+
+/// Struct for keeping Arrow IPC writers for each of the output tables.
+pub struct ArrowIpcOutputs {
+    batcher: EntryBatcher,
+    writers: HashMap<String, IpcWriter>,
+    basedir: PathBuf,
+    format: ArrowIpcFormat,
+}
+
+/// Initialize Arrow IPC output files, with `batch_size` rows in each record batch and the
+/// optional columns given by `schema`.
+pub fn initialize_outputs_arrow(
+    basedir: &Path,
+    format: ArrowIpcFormat,
+    batch_size: usize,
+    schema: &SchemaOptions,
+) -> Result<ArrowIpcOutputs, Box<dyn Error>> {
+    if batch_size == 0 {
+        return Err(From::from("batch size must be greater than 0"));
+    }
+    let mut outputs = ArrowIpcOutputs {
+        batcher: EntryBatcher::with_schema(schema.clone(), batch_size),
+        writers: HashMap::new(),
+        basedir: basedir.to_path_buf(),
+        format,
+    };
+    for (name, arrow_schema) in outputs.batcher.schemas() {
+        outputs.create_writer(name, &arrow_schema)?;
+    }
+    Ok(outputs)
+}
+
+impl ArrowIpcOutputs {
+    fn create_writer(&mut self, name: &str, schema: &SchemaRef) -> Result<(), Box<dyn Error>> {
+        let extension = match self.format {
+            ArrowIpcFormat::File => "arrow",
+            ArrowIpcFormat::Stream => "arrows",
+        };
+        let path = self.basedir.join(format!("{}.{}", name, extension));
+        let writer = IpcWriter::create(&path, schema, self.format)?;
+        self.writers.insert(name.to_string(), writer);
+        Ok(())
+    }
+
+    fn write(&mut self, batch: Option<TableBatch>) -> Result<(), Box<dyn Error>> {
+        if let Some((name, batch)) = batch {
+            // Property tables other than the built-in ones are created with their first batch.
+            if !self.writers.contains_key(&*name) {
+                self.create_writer(&name, &batch.schema())?;
+            }
+            self.writers.get_mut(&*name).unwrap().write(&batch)?;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for ArrowIpcOutputs {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_uniparc(uniparc);
+        self.write(batch)
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_xref(xref);
+        self.write(batch)
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_property(property);
+        self.write(batch)
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_domain(domain);
+        self.write(batch)
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        for batch in self.batcher.finish() {
+            self.write(Some(batch))?;
+        }
+        for writer in self.writers.values_mut() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
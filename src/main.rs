@@ -3,18 +3,61 @@ extern crate clap;
 extern crate uniparc_xml_parser;
 
 use std::env;
-use std::io;
-use std::path::PathBuf;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Arc;
+use std::thread;
 
 use clap::{App, Arg};
-use uniparc_xml_parser::{initialize_outputs, initialize_outputs_compressed};
+use uniparc_xml_parser::{
+    initialize_outputs, initialize_outputs_arrow, initialize_outputs_compressed,
+    initialize_outputs_domain_fasta, initialize_outputs_fasta, initialize_outputs_parquet,
+    initialize_schema, open_inputs, read_taxa, ArrowIpcFormat, CsvFormat, DbFilter, DbType,
+    DomainFastaOptions, DomainFilter, ErrorPolicy, Escape, FastaHeader, FastaOptions, FilterScope,
+    ParquetCompression, ParquetOptions, ParseOptions, PropertyMapping, Quarantine, QuoteStyle,
+    RunOptions, SequenceCheck, SequenceDigests, Sink, TaxonFilter, Taxonomy, UnknownProperties,
+};
+
+/// Parse a single-byte command-line option, such as the delimiter.
+fn parse_char(value: &str, name: &str) -> u8 {
+    match value {
+        "tab" | "\\t" => b'\t',
+        _ if value.len() == 1 => value.as_bytes()[0],
+        _ => {
+            println!(
+                "Invalid {}: '{}' (must be a single ASCII character).",
+                name, value
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// Check that a numeric command-line option is greater than 0.
+fn is_positive(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(format!("'{}' is not a positive integer", value)),
+    }
+}
 
 fn main() {
     let matches = App::new("UniParc XML Parser")
         .version(crate_version!())
         .author("Alexey S. <alex.strokach@utoronto.ca>")
         .about("Parse the gynormous UniParc XML file.")
+        .arg(
+            Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .takes_value(true)
+                .multiple(true)
+                .help("Input XML file(s). Reads from stdin if not provided.")
+                .required(false),
+        )
         .arg(
             Arg::with_name("basedir")
                 .short("d")
@@ -23,13 +66,328 @@ fn main() {
                 .help("Directory in which to output the files.")
                 .required(false),
         )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .possible_values(&[
+                    "tsv",
+                    "parquet",
+                    "arrow",
+                    "arrow-stream",
+                    "fasta",
+                    "domain-fasta",
+                ])
+                .default_value("tsv")
+                .help("Format(s) of the output files, e.g. 'tsv,parquet'."),
+        )
         .arg(
             Arg::with_name("use-compression")
-                .help("Whether or not the output files should be compressed.")
+                .help("Whether or not the output TSV files should be compressed.")
                 .short("c")
                 .long("use-compression")
                 .required(false),
         )
+        .arg(
+            Arg::with_name("row-group-size")
+                .long("row-group-size")
+                .takes_value(true)
+                .default_value("1048576")
+                .validator(is_positive)
+                .help("Maximum number of rows in each row group of the output Parquet files."),
+        )
+        .arg(
+            Arg::with_name("batch-size")
+                .long("batch-size")
+                .takes_value(true)
+                .default_value("8192")
+                .validator(is_positive)
+                .help("Number of rows in each record batch of the output Arrow files."),
+        )
+        .arg(
+            Arg::with_name("parquet-compression")
+                .long("parquet-compression")
+                .takes_value(true)
+                .possible_values(&["zstd", "snappy", "none"])
+                .default_value("zstd")
+                .help("Compression codec used for the output Parquet files."),
+        )
+        .arg(
+            Arg::with_name("fasta-header")
+                .long("fasta-header")
+                .takes_value(true)
+                .default_value("{uniparc_id}")
+                .help(
+                    "Template of the header of each record of the output FASTA file, \
+                     e.g. '{uniparc_id} {protein_name} OX={ncbi_taxonomy_id}'. Fields are \
+                     columns of the uniparc table other than sequence, including enabled \
+                     digests, built-in property names, and property types given to \
+                     --property-table.",
+                ),
+        )
+        .arg(
+            Arg::with_name("fasta-width")
+                .long("fasta-width")
+                .takes_value(true)
+                .default_value("60")
+                .help("Number of residues on each line of the output FASTA file (0 to not wrap)."),
+        )
+        .arg(
+            Arg::with_name("domain-databases")
+                .long("domain-databases")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("DATABASE")
+                .help(
+                    "Databases whose domains are written to the output domain FASTA file, \
+                     e.g. 'Pfam,Gene3D', ignoring case. Defaults to all databases.",
+                ),
+        )
+        .arg(
+            Arg::with_name("deduplicate-domains")
+                .long("deduplicate-domains")
+                .help(
+                    "Write each distinct domain sequence to the output domain FASTA file once. \
+                     This keeps a digest of each distinct sequence in memory, roughly 20 to 40 \
+                     bytes per sequence.",
+                ),
+        )
+        .arg(
+            Arg::with_name("delimiter")
+                .long("delimiter")
+                .takes_value(true)
+                .default_value("tab")
+                .help("Field delimiter of the output TSV files ('tab' or a single character)."),
+        )
+        .arg(
+            Arg::with_name("quote")
+                .long("quote")
+                .takes_value(true)
+                .default_value("\"")
+                .help("Quote character of the output TSV files."),
+        )
+        .arg(
+            Arg::with_name("quote-style")
+                .long("quote-style")
+                .takes_value(true)
+                .possible_values(&["always", "non-numeric", "necessary", "never"])
+                .default_value("non-numeric")
+                .help("Which fields of the output TSV files are quoted."),
+        )
+        .arg(
+            Arg::with_name("escape")
+                .long("escape")
+                .takes_value(true)
+                .possible_values(&["double", "backslash"])
+                .default_value("double")
+                .help("How quotes and special characters are escaped in the output TSV files."),
+        )
+        .arg(
+            Arg::with_name("null")
+                .long("null")
+                .takes_value(true)
+                .default_value("")
+                .help("String representing missing values in the output TSV files."),
+        )
+        .arg(
+            Arg::with_name("line-terminator")
+                .long("line-terminator")
+                .takes_value(true)
+                .possible_values(&["lf", "crlf"])
+                .default_value("lf")
+                .help("Line terminator of the output TSV files."),
+        )
+        .arg(
+            Arg::with_name("header")
+                .long("header")
+                .help("Write a header row with the column names to each output TSV file."),
+        )
+        .arg(
+            Arg::with_name("include-inactive")
+                .long("include-inactive")
+                .help(
+                    "Keep inactive cross-references and their properties. \
+                     Use the 'active' column of 'xref' to tell them apart.",
+                ),
+        )
+        .arg(
+            Arg::with_name("property-table")
+                .long("property-table")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("TYPE=TABLE")
+                .help(
+                    "Write properties of the given type to the given table, \
+                     e.g. 'UniProtKB_isoform=isoform'. Can be given multiple times.",
+                ),
+        )
+        .arg(
+            Arg::with_name("unknown-properties")
+                .long("unknown-properties")
+                .takes_value(true)
+                .possible_values(&["generic", "per-type"])
+                .default_value("generic")
+                .help(
+                    "Where to write properties of types without a table of their own. \
+                     'generic' writes them to the 'property' table, \
+                     'per-type' to a 'property_<type>' table for each type.",
+                ),
+        )
+        .arg(
+            Arg::with_name("verify-sequences")
+                .long("verify-sequences")
+                .takes_value(true)
+                .possible_values(&["off", "report", "reject"])
+                .default_value("off")
+                .help(
+                    "Check the length and CRC64 checksum of each sequence. \
+                     'report' logs mismatches, 'reject' treats them as errors (see --on-error).",
+                ),
+        )
+        .arg(
+            Arg::with_name("require-db")
+                .long("require-db")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("DATABASE")
+                .help(
+                    "Only keep entries with a cross-reference to one of the given databases, \
+                     e.g. 'UniProtKB/Swiss-Prot,PDB'.",
+                ),
+        )
+        .arg(
+            Arg::with_name("exclude-db")
+                .long("exclude-db")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("DATABASE")
+                .help("Drop entries with a cross-reference to any of the given databases."),
+        )
+        .arg(
+            Arg::with_name("xref-db")
+                .long("xref-db")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("DATABASE")
+                .help(
+                    "Only write cross-references (and their properties) to the given databases. \
+                     Entries without such cross-references are still written.",
+                ),
+        )
+        .arg(
+            Arg::with_name("require-domain-db")
+                .long("require-domain-db")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("DATABASE")
+                .help(
+                    "Only keep entries with a domain from one of the given member databases, \
+                     e.g. 'Pfam,Gene3D', ignoring case. With --require-interpro, entries with \
+                     a domain matching either option are kept.",
+                ),
+        )
+        .arg(
+            Arg::with_name("require-interpro")
+                .long("require-interpro")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("INTERPRO_ID")
+                .help(
+                    "Only keep entries with a domain of one of the given InterPro IDs, \
+                     e.g. 'IPR000971'. With --require-domain-db, entries with a domain \
+                     matching either option are kept.",
+                ),
+        )
+        .arg(
+            Arg::with_name("taxon")
+                .long("taxon")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .value_name("TAXON_ID")
+                .help(
+                    "Only keep entries of the given NCBI taxa, e.g. '9606,10090'. With --taxdump, \
+                     taxa can also be given by their scientific name, e.g. 'Mammalia', \
+                     and entries of their descendants are kept as well.",
+                ),
+        )
+        .arg(
+            Arg::with_name("taxon-file")
+                .long("taxon-file")
+                .takes_value(true)
+                .help("Only keep entries of the NCBI taxa listed in the given file."),
+        )
+        .arg(
+            Arg::with_name("taxon-scope")
+                .long("taxon-scope")
+                .takes_value(true)
+                .possible_values(&["entries", "xrefs"])
+                .default_value("entries")
+                .help(
+                    "Whether --taxon keeps whole entries with a cross-reference of the given taxa, \
+                     or only those cross-references.",
+                ),
+        )
+        .arg(
+            Arg::with_name("taxdump")
+                .long("taxdump")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Directory with the nodes.dmp and names.dmp files of an NCBI taxdump."),
+        )
+        .arg(
+            Arg::with_name("taxon-lineage")
+                .long("taxon-lineage")
+                .requires("taxdump")
+                .help(
+                    "Add rank and lineage columns to the table of NCBI taxonomy IDs \
+                     (ncbi_taxonomy_id by default), using the taxonomy given by --taxdump.",
+                ),
+        )
+        .arg(
+            Arg::with_name("digests")
+                .long("digests")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .possible_values(SequenceDigests::NAMES)
+                .value_name("DIGEST")
+                .help(
+                    "Compute the given digests of each sequence, written to the \
+                     'sequence_<DIGEST>' columns of the uniparc table, e.g. 'md5,refget'.",
+                ),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .short("t")
+                .long("threads")
+                .takes_value(true)
+                .help(
+                    "Number of threads used to parse entries. Defaults to the number of CPUs. \
+                     Only with 1 thread are entries written without first copying every value.",
+                ),
+        )
+        .arg(
+            Arg::with_name("on-error")
+                .long("on-error")
+                .takes_value(true)
+                .possible_values(&["abort", "skip", "quarantine"])
+                .default_value("abort")
+                .help(
+                    "What to do with entries that fail to parse. \
+                     'quarantine' writes them to 'rejected.xml' in the output directory.",
+                ),
+        )
         .get_matches();
 
     let basedir = match matches.value_of("basedir") {
@@ -38,31 +396,272 @@ fn main() {
     };
     println!("basedir: {:?}", basedir);
 
+    let formats: Vec<&str> = matches.values_of("format").unwrap().collect();
+    println!("formats: {:?}", formats);
+
     let use_compression: bool = matches.is_present("use-compression");
     println!("use_compression: {}", use_compression);
 
-    let input = io::stdin();
+    let parquet_options = ParquetOptions {
+        row_group_size: value_t_or_exit!(matches, "row-group-size", usize),
+        compression: match matches.value_of("parquet-compression").unwrap() {
+            "snappy" => ParquetCompression::Snappy,
+            "none" => ParquetCompression::Uncompressed,
+            _ => ParquetCompression::Zstd,
+        },
+    };
 
-    match use_compression {
-        true => {
-            let handlers = initialize_outputs_compressed(PathBuf::from(basedir));
-            match uniparc_xml_parser::run(input, handlers) {
-                Ok(count) => println!("Processed {} elements.", count),
-                Err(err) => {
-                    println!("Failed with error: {}.", err);
+    let input_files: Vec<PathBuf> = match matches.values_of("input") {
+        Some(values) => values.map(PathBuf::from).collect(),
+        None => Vec::new(),
+    };
+    println!("input_files: {:?}", input_files);
+
+    let inputs = match open_inputs(&input_files) {
+        Ok(inputs) => inputs,
+        Err(err) => {
+            println!("Failed to open input files: {}.", err);
+            process::exit(1);
+        }
+    };
+
+    let on_error = match matches.value_of("on-error").unwrap() {
+        "skip" => ErrorPolicy::Skip,
+        "quarantine" => match Quarantine::create(&basedir) {
+            Ok(quarantine) => ErrorPolicy::Quarantine(quarantine),
+            Err(err) => {
+                println!("Failed to create quarantine files: {}.", err);
+                process::exit(1);
+            }
+        },
+        _ => ErrorPolicy::Abort,
+    };
+    let threads = if matches.is_present("threads") {
+        value_t_or_exit!(matches, "threads", usize)
+    } else {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    };
+    println!("threads: {}", threads);
+    let mut property_mapping = PropertyMapping::default();
+    property_mapping.unknown = match matches.value_of("unknown-properties").unwrap() {
+        "per-type" => UnknownProperties::PerType,
+        _ => UnknownProperties::Generic,
+    };
+    for value in matches.values_of("property-table").into_iter().flatten() {
+        let result = match value.find('=') {
+            Some(index) => property_mapping.insert(&value[..index], &value[index + 1..]),
+            None => Err(From::from("expected 'TYPE=TABLE'")),
+        };
+        if let Err(err) = result {
+            println!("Invalid property table '{}': {}.", value, err);
+            process::exit(1);
+        }
+    }
+    let mut digests = SequenceDigests::default();
+    for name in matches.values_of("digests").into_iter().flatten() {
+        digests.enable(name);
+    }
+    let db_types = |name: &str| -> Vec<DbType> {
+        let db_types = matches
+            .values_of(name)
+            .into_iter()
+            .flatten()
+            .map(DbType::from_name)
+            .collect::<Vec<_>>();
+        for db_type in &db_types {
+            if let DbType::Other(ref name) = *db_type {
+                println!("Warning: unknown database '{}'.", name);
+            }
+        }
+        db_types
+    };
+    let values = |name: &str| -> Vec<String> {
+        matches
+            .values_of(name)
+            .into_iter()
+            .flatten()
+            .map(String::from)
+            .collect()
+    };
+    let domains = DomainFilter {
+        databases: values("require-domain-db"),
+        interpro_ids: values("require-interpro"),
+    };
+    let databases = DbFilter {
+        require: db_types("require-db"),
+        exclude: db_types("exclude-db"),
+        xrefs: db_types("xref-db"),
+    };
+    let taxonomy = matches
+        .value_of("taxdump")
+        .map(|path| match Taxonomy::load(Path::new(path)) {
+            Ok(taxonomy) => {
+                println!("taxonomy: {} taxa", taxonomy.len());
+                Arc::new(taxonomy)
+            }
+            Err(err) => {
+                println!("Failed to read taxdump '{}': {}.", path, err);
+                process::exit(1);
+            }
+        });
+    let taxa = if matches.is_present("taxon") || matches.is_present("taxon-file") {
+        let mut taxa = Vec::new();
+        for value in matches.values_of("taxon").into_iter().flatten() {
+            let taxon = match (value.parse().ok(), taxonomy.as_ref()) {
+                (Some(taxon), _) => Some(taxon),
+                (None, Some(taxonomy)) => taxonomy.find(value),
+                (None, None) => None,
+            };
+            match taxon {
+                Some(taxon) => taxa.push(taxon),
+                None => {
+                    println!("Invalid taxon '{}'.", value);
                     process::exit(1);
                 }
             }
         }
-        false => {
-            let handlers = initialize_outputs(PathBuf::from(basedir));
-            match uniparc_xml_parser::run(input, handlers) {
-                Ok(count) => println!("Processed {} elements.", count),
+        if let Some(path) = matches.value_of("taxon-file") {
+            match File::open(path)
+                .map_err(From::from)
+                .and_then(|file| read_taxa(BufReader::new(file)))
+            {
+                Ok(file_taxa) => taxa.extend(file_taxa),
                 Err(err) => {
-                    println!("Failed with error: {}.", err);
+                    println!("Failed to read taxon file '{}': {}.", path, err);
                     process::exit(1);
                 }
             }
         }
+        let scope = match matches.value_of("taxon-scope").unwrap() {
+            "xrefs" => FilterScope::XRefs,
+            _ => FilterScope::Entries,
+        };
+        match taxonomy {
+            Some(ref taxonomy) => Some(TaxonFilter::new(taxonomy.descendants(taxa), scope)),
+            None => Some(TaxonFilter::new(taxa, scope)),
+        }
+    } else {
+        None
+    };
+    let parse_options = ParseOptions {
+        include_inactive: matches.is_present("include-inactive"),
+        properties: property_mapping,
+        verify_sequences: match matches.value_of("verify-sequences").unwrap() {
+            "report" => SequenceCheck::Report,
+            "reject" => SequenceCheck::Reject,
+            _ => SequenceCheck::Off,
+        },
+        digests,
+        domains,
+        databases,
+        taxa,
+        taxonomy: if matches.is_present("taxon-lineage") {
+            taxonomy
+        } else {
+            None
+        },
+    };
+    let options = RunOptions {
+        on_error,
+        parse: parse_options,
+        threads,
+    };
+    let schema = options.parse.schema();
+
+    let fasta_header = matches.value_of("fasta-header").unwrap();
+    let fasta_options = FastaOptions {
+        header: match FastaHeader::parse(fasta_header, &options.parse) {
+            Ok(header) => header,
+            Err(err) => {
+                println!("Invalid FASTA header: {}.", err);
+                process::exit(1);
+            }
+        },
+        line_width: value_t_or_exit!(matches, "fasta-width", usize),
+    };
+    let domain_fasta_options = DomainFastaOptions {
+        databases: matches
+            .values_of("domain-databases")
+            .into_iter()
+            .flatten()
+            .map(String::from)
+            .collect(),
+        deduplicate: matches.is_present("deduplicate-domains"),
+        line_width: fasta_options.line_width,
+    };
+
+    let csv_format = CsvFormat {
+        delimiter: parse_char(matches.value_of("delimiter").unwrap(), "delimiter"),
+        quote: parse_char(matches.value_of("quote").unwrap(), "quote"),
+        quote_style: match matches.value_of("quote-style").unwrap() {
+            "always" => QuoteStyle::Always,
+            "necessary" => QuoteStyle::Necessary,
+            "never" => QuoteStyle::Never,
+            _ => QuoteStyle::NonNumeric,
+        },
+        escape: match matches.value_of("escape").unwrap() {
+            "backslash" => Escape::Backslash,
+            _ => Escape::Double,
+        },
+        null: matches.value_of("null").unwrap().to_string(),
+        line_terminator: match matches.value_of("line-terminator").unwrap() {
+            "crlf" => String::from("\r\n"),
+            _ => String::from("\n"),
+        },
+        header: matches.is_present("header"),
+    };
+    if let Err(err) = csv_format.validate() {
+        println!("Invalid TSV format: {}.", err);
+        process::exit(1);
+    }
+
+    let batch_size = value_t_or_exit!(matches, "batch-size", usize);
+
+    if let Err(err) = initialize_schema(&basedir, &schema) {
+        println!("Failed to write schema file: {}.", err);
+        process::exit(1);
+    }
+
+    let mut sinks: Vec<Box<dyn Sink>> = Vec::new();
+    for &format in &formats {
+        let sink: Result<Box<dyn Sink>, Box<dyn Error>> = match format {
+            "parquet" => initialize_outputs_parquet(&basedir, &parquet_options, &schema)
+                .map(|sink| Box::new(sink) as Box<dyn Sink>),
+            "arrow" => {
+                initialize_outputs_arrow(&basedir, ArrowIpcFormat::File, batch_size, &schema)
+                    .map(|sink| Box::new(sink) as Box<dyn Sink>)
+            }
+            "arrow-stream" => {
+                initialize_outputs_arrow(&basedir, ArrowIpcFormat::Stream, batch_size, &schema)
+                    .map(|sink| Box::new(sink) as Box<dyn Sink>)
+            }
+            "fasta" => initialize_outputs_fasta(&basedir, &fasta_options)
+                .map(|sink| Box::new(sink) as Box<dyn Sink>),
+            "domain-fasta" => initialize_outputs_domain_fasta(&basedir, &domain_fasta_options)
+                .map(|sink| Box::new(sink) as Box<dyn Sink>),
+            _ if use_compression => {
+                initialize_outputs_compressed(basedir.clone(), csv_format.clone(), schema.clone())
+                    .map(|sink| Box::new(sink) as Box<dyn Sink>)
+            }
+            _ => initialize_outputs(basedir.clone(), csv_format.clone(), schema.clone())
+                .map(|sink| Box::new(sink) as Box<dyn Sink>),
+        };
+        match sink {
+            Ok(sink) => sinks.push(sink),
+            Err(err) => {
+                println!("Failed to create {} output files: {}.", format, err);
+                process::exit(1);
+            }
+        }
+    }
+
+    let result = uniparc_xml_parser::run_inputs(inputs, sinks, options);
+
+    match result {
+        Ok(count) => println!("Processed {} elements.", count),
+        Err(err) => {
+            println!("Failed with error: {}.", err);
+            process::exit(1);
+        }
     }
 }
@@ -1,31 +1,55 @@
-#[derive(Default)]
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+
+use db_type::DbType;
+
+use properties::Properties;
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Uniparc {
     pub uniparc_id: String,
     pub sequence: String,
     pub sequence_length: u32,
     pub sequence_checksum: String,
+    /// Digests of the sequence, if they are enabled in
+    /// [`SequenceDigests`](struct.SequenceDigests.html).
+    pub sequence_md5: Option<String>,
+    pub sequence_sha256: Option<String>,
+    pub sequence_refget: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct UniparcXRef {
     pub uniparc_id: String,
     pub xref_id: u64,
-    pub db_type: String,
+    pub db_type: DbType,
     pub db_id: String,
-    pub version_i: String,
-    pub active: String,
-    pub version: String,
-    pub created: String,
-    pub last: String,
+    pub version_i: u32,
+    pub active: bool,
+    /// Version of the sequence in the cross-referenced database, if it has versions.
+    pub version: Option<u32>,
+    pub created: NaiveDate,
+    pub last: NaiveDate,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct UniparcProperty {
     pub uniparc_id: String,
     pub xref_id: u64,
     pub property: String,
     pub value: String,
+    /// Rank and lineage of the taxon of an NCBI taxonomy ID, if a taxonomy is given in the
+    /// `ParseOptions`. Only written to the table of `NCBI_taxonomy_id` properties, and only
+    /// if a taxonomy is given. Both are shared by all properties of the same taxon.
+    pub rank: Option<Arc<str>>,
+    pub lineage: Option<Arc<str>>,
+    /// Name of the table to which the property is written; not a column of that table.
+    pub table: String,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UniparcDomain {
     pub uniparc_id: String,
     pub database: String,
@@ -35,3 +59,228 @@ pub struct UniparcDomain {
     pub domain_start: u32,
     pub domain_end: u32,
 }
+
+/// All data extracted from a single `<entry>` element.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UniparcEntry {
+    pub uniparc: Uniparc,
+    pub xrefs: Vec<UniparcXRef>,
+    pub properties: Properties<Vec<UniparcProperty>>,
+    /// Properties written to tables other than the built-in ones, as given by their `table`.
+    pub extra_properties: Vec<UniparcProperty>,
+    pub domains: Vec<UniparcDomain>,
+}
+
+/// Borrowed version of [`Uniparc`](struct.Uniparc.html).
+///
+/// Text fields borrow from the raw XML of the entry wherever possible, and are only copied
+/// when they have to be unescaped or otherwise modified.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UniparcRef<'a> {
+    pub uniparc_id: Cow<'a, str>,
+    pub sequence: Cow<'a, str>,
+    pub sequence_length: u32,
+    pub sequence_checksum: Cow<'a, str>,
+    pub sequence_md5: Option<Cow<'a, str>>,
+    pub sequence_sha256: Option<Cow<'a, str>>,
+    pub sequence_refget: Option<Cow<'a, str>>,
+}
+
+/// Borrowed version of [`UniparcXRef`](struct.UniparcXRef.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniparcXRefRef<'a> {
+    pub uniparc_id: Cow<'a, str>,
+    pub xref_id: u64,
+    pub db_type: DbType,
+    pub db_id: Cow<'a, str>,
+    pub version_i: u32,
+    pub active: bool,
+    pub version: Option<u32>,
+    pub created: NaiveDate,
+    pub last: NaiveDate,
+}
+
+/// Borrowed version of [`UniparcProperty`](struct.UniparcProperty.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniparcPropertyRef<'a> {
+    pub uniparc_id: Cow<'a, str>,
+    pub xref_id: u64,
+    pub property: Cow<'a, str>,
+    pub value: Cow<'a, str>,
+    pub rank: Option<Arc<str>>,
+    pub lineage: Option<Arc<str>>,
+    pub table: Cow<'a, str>,
+}
+
+/// Borrowed version of [`UniparcDomain`](struct.UniparcDomain.html).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UniparcDomainRef<'a> {
+    pub uniparc_id: Cow<'a, str>,
+    pub database: Cow<'a, str>,
+    pub database_id: Cow<'a, str>,
+    pub interpro_name: Cow<'a, str>,
+    pub interpro_id: Cow<'a, str>,
+    pub domain_start: u32,
+    pub domain_end: u32,
+}
+
+/// All data extracted from a single `<entry>` element, borrowing from its raw XML.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniparcEntryRef<'a> {
+    pub uniparc: UniparcRef<'a>,
+    pub xrefs: Vec<UniparcXRefRef<'a>>,
+    pub properties: Properties<Vec<UniparcPropertyRef<'a>>>,
+    pub extra_properties: Vec<UniparcPropertyRef<'a>>,
+    pub domains: Vec<UniparcDomainRef<'a>>,
+}
+
+impl<'a> Default for UniparcEntryRef<'a> {
+    fn default() -> UniparcEntryRef<'a> {
+        UniparcEntryRef {
+            uniparc: UniparcRef::default(),
+            xrefs: Vec::new(),
+            properties: Properties::from_fn(|_| Vec::new()),
+            extra_properties: Vec::new(),
+            domains: Vec::new(),
+        }
+    }
+}
+
+impl<'a> UniparcRef<'a> {
+    pub fn into_owned(self) -> Uniparc {
+        Uniparc {
+            uniparc_id: self.uniparc_id.into_owned(),
+            sequence: self.sequence.into_owned(),
+            sequence_length: self.sequence_length,
+            sequence_checksum: self.sequence_checksum.into_owned(),
+            sequence_md5: self.sequence_md5.map(Cow::into_owned),
+            sequence_sha256: self.sequence_sha256.map(Cow::into_owned),
+            sequence_refget: self.sequence_refget.map(Cow::into_owned),
+        }
+    }
+}
+
+impl<'a> UniparcXRefRef<'a> {
+    pub fn into_owned(self) -> UniparcXRef {
+        UniparcXRef {
+            uniparc_id: self.uniparc_id.into_owned(),
+            xref_id: self.xref_id,
+            db_type: self.db_type,
+            db_id: self.db_id.into_owned(),
+            version_i: self.version_i,
+            active: self.active,
+            version: self.version,
+            created: self.created,
+            last: self.last,
+        }
+    }
+}
+
+impl<'a> UniparcPropertyRef<'a> {
+    pub fn into_owned(self) -> UniparcProperty {
+        UniparcProperty {
+            uniparc_id: self.uniparc_id.into_owned(),
+            xref_id: self.xref_id,
+            property: self.property.into_owned(),
+            value: self.value.into_owned(),
+            rank: self.rank,
+            lineage: self.lineage,
+            table: self.table.into_owned(),
+        }
+    }
+}
+
+impl<'a> UniparcDomainRef<'a> {
+    pub fn into_owned(self) -> UniparcDomain {
+        UniparcDomain {
+            uniparc_id: self.uniparc_id.into_owned(),
+            database: self.database.into_owned(),
+            database_id: self.database_id.into_owned(),
+            interpro_name: self.interpro_name.into_owned(),
+            interpro_id: self.interpro_id.into_owned(),
+            domain_start: self.domain_start,
+            domain_end: self.domain_end,
+        }
+    }
+}
+
+impl Uniparc {
+    /// Borrowed version of the record, without copying any data.
+    pub fn as_ref<'a>(&'a self) -> UniparcRef<'a> {
+        UniparcRef {
+            uniparc_id: Cow::Borrowed(&self.uniparc_id),
+            sequence: Cow::Borrowed(&self.sequence),
+            sequence_length: self.sequence_length,
+            sequence_checksum: Cow::Borrowed(&self.sequence_checksum),
+            sequence_md5: self.sequence_md5.as_deref().map(Cow::Borrowed),
+            sequence_sha256: self.sequence_sha256.as_deref().map(Cow::Borrowed),
+            sequence_refget: self.sequence_refget.as_deref().map(Cow::Borrowed),
+        }
+    }
+}
+
+impl UniparcXRef {
+    /// Borrowed version of the record, without copying any data.
+    pub fn as_ref<'a>(&'a self) -> UniparcXRefRef<'a> {
+        UniparcXRefRef {
+            uniparc_id: Cow::Borrowed(&self.uniparc_id),
+            xref_id: self.xref_id,
+            db_type: self.db_type.clone(),
+            db_id: Cow::Borrowed(&self.db_id),
+            version_i: self.version_i,
+            active: self.active,
+            version: self.version,
+            created: self.created,
+            last: self.last,
+        }
+    }
+}
+
+impl UniparcProperty {
+    /// Borrowed version of the record, without copying any data.
+    pub fn as_ref<'a>(&'a self) -> UniparcPropertyRef<'a> {
+        UniparcPropertyRef {
+            uniparc_id: Cow::Borrowed(&self.uniparc_id),
+            xref_id: self.xref_id,
+            property: Cow::Borrowed(&self.property),
+            value: Cow::Borrowed(&self.value),
+            rank: self.rank.clone(),
+            lineage: self.lineage.clone(),
+            table: Cow::Borrowed(&self.table),
+        }
+    }
+}
+
+impl UniparcDomain {
+    /// Borrowed version of the record, without copying any data.
+    pub fn as_ref<'a>(&'a self) -> UniparcDomainRef<'a> {
+        UniparcDomainRef {
+            uniparc_id: Cow::Borrowed(&self.uniparc_id),
+            database: Cow::Borrowed(&self.database),
+            database_id: Cow::Borrowed(&self.database_id),
+            interpro_name: Cow::Borrowed(&self.interpro_name),
+            interpro_id: Cow::Borrowed(&self.interpro_id),
+            domain_start: self.domain_start,
+            domain_end: self.domain_end,
+        }
+    }
+}
+
+impl<'a> UniparcEntryRef<'a> {
+    /// Copy all borrowed data, producing an entry which no longer borrows the raw XML.
+    pub fn into_owned(self) -> UniparcEntry {
+        UniparcEntry {
+            uniparc: self.uniparc.into_owned(),
+            xrefs: self.xrefs.into_iter().map(|x| x.into_owned()).collect(),
+            properties: self
+                .properties
+                .map(|p| p.into_iter().map(|p| p.into_owned()).collect()),
+            extra_properties: self
+                .extra_properties
+                .into_iter()
+                .map(|p| p.into_owned())
+                .collect(),
+            domains: self.domains.into_iter().map(|d| d.into_owned()).collect(),
+        }
+    }
+}
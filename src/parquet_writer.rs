@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use arrow_schema::SchemaRef;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use batch::{EntryBatcher, TableBatch};
+use model::{Uniparc, UniparcDomain, UniparcProperty, UniparcXRef};
+use schema::SchemaOptions;
+use sink::Sink;
+
+/// Compression codec used for Parquet output files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParquetCompression {
+    Uncompressed,
+    Snappy,
+    Zstd,
+}
+
+/// Options for writing Parquet files.
+#[derive(Debug, Clone)]
+pub struct ParquetOptions {
+    /// Maximum number of rows in each row group.
+    pub row_group_size: usize,
+    pub compression: ParquetCompression,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> ParquetOptions {
+        ParquetOptions {
+            row_group_size: 1024 * 1024,
+            compression: ParquetCompression::Zstd,
+        }
+    }
+}
+
+impl ParquetOptions {
+    /// Check that the options can be used to write Parquet files.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.row_group_size == 0 {
+            return Err(From::from("row group size must be greater than 0"));
+        }
+        Ok(())
+    }
+
+    fn writer_properties(&self) -> WriterProperties {
+        let compression = match self.compression {
+            ParquetCompression::Uncompressed => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+        };
+        WriterProperties::builder()
+            .set_max_row_group_size(self.row_group_size)
+            .set_compression(compression)
+            .build()
+    }
+}
+
+/// Number of rows that are accumulated before being passed to the Parquet writer.
+const BATCH_SIZE: usize = 8192;
+
+/// Struct for keeping Parquet writers for each of the output tables.
+pub struct ParquetOutputs {
+    batcher: EntryBatcher,
+    writers: HashMap<String, ArrowWriter<File>>,
+    basedir: PathBuf,
+    writer_properties: WriterProperties,
+}
+
+/// Initialize Parquet output files, with the optional columns given by `schema`.
+pub fn initialize_outputs_parquet(
+    basedir: &Path,
+    options: &ParquetOptions,
+    schema: &SchemaOptions,
+) -> Result<ParquetOutputs, Box<dyn Error>> {
+    options.validate()?;
+    let batch_size = BATCH_SIZE.min(options.row_group_size);
+    let mut outputs = ParquetOutputs {
+        batcher: EntryBatcher::with_schema(schema.clone(), batch_size),
+        writers: HashMap::new(),
+        basedir: basedir.to_path_buf(),
+        writer_properties: options.writer_properties(),
+    };
+    for (name, arrow_schema) in outputs.batcher.schemas() {
+        outputs.create_writer(name, arrow_schema)?;
+    }
+    Ok(outputs)
+}
+
+impl ParquetOutputs {
+    fn create_writer(&mut self, name: &str, schema: SchemaRef) -> Result<(), Box<dyn Error>> {
+        let file = File::create(self.basedir.join(format!("{}.parquet", name)))?;
+        let writer = ArrowWriter::try_new(file, schema, Some(self.writer_properties.clone()))?;
+        self.writers.insert(name.to_string(), writer);
+        Ok(())
+    }
+
+    fn write(&mut self, batch: Option<TableBatch>) -> Result<(), Box<dyn Error>> {
+        if let Some((name, batch)) = batch {
+            // Property tables other than the built-in ones are created with their first batch.
+            if !self.writers.contains_key(&*name) {
+                self.create_writer(&name, batch.schema())?;
+            }
+            self.writers.get_mut(&*name).unwrap().write(&batch)?;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for ParquetOutputs {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_uniparc(uniparc);
+        self.write(batch)
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_xref(xref);
+        self.write(batch)
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_property(property);
+        self.write(batch)
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        let batch = self.batcher.push_domain(domain);
+        self.write(batch)
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        for batch in self.batcher.finish() {
+            self.write(Some(batch))?;
+        }
+        for (_, writer) in self.writers.drain() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use error::ParseError;
+use filter::filter_entry;
+use model::UniparcEntry;
+use reader::{parse_entry_ref, ParseOptions};
+use splitter::RawEntry;
+
+/// Number of entries sent to a worker thread at a time.
+const CHUNK_SIZE: usize = 64;
+
+/// Number of chunks per worker thread which may be split ahead of the chunk that is to be
+/// handled next.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// Numbered chunk of raw entries, or the error which ended the input.
+type Chunk = (usize, io::Result<Vec<RawEntry>>);
+
+/// Numbered chunk of raw entries together with the result of parsing them.
+type ParsedChunk = (
+    usize,
+    io::Result<Vec<(RawEntry, Result<UniparcEntry, ParseError>)>>,
+);
+
+/// Index of the next chunk to be handled, which limits how far the splitter thread may run
+/// ahead of it.
+///
+/// Without this limit, a single slow chunk would hold back all chunks after it, which would
+/// pile up in memory while the other workers keep parsing.
+struct Window {
+    size: usize,
+    next: Mutex<usize>,
+    advanced: Condvar,
+}
+
+impl Window {
+    fn new(size: usize) -> Window {
+        Window {
+            size,
+            next: Mutex::new(0),
+            advanced: Condvar::new(),
+        }
+    }
+
+    /// Block until the chunk with index `index` is inside the window.
+    fn wait(&self, index: usize) {
+        let mut next = self.next.lock().unwrap();
+        while index >= next.saturating_add(self.size) {
+            next = self.advanced.wait(next).unwrap();
+        }
+    }
+
+    /// Move the start of the window to `next`.
+    fn advance(&self, next: usize) {
+        *self.next.lock().unwrap() = next;
+        self.advanced.notify_all();
+    }
+}
+
+/// Parse the raw entries from `entries`, e.g. an `EntrySplitter`, using `threads` worker threads.
+///
+/// One thread reads the raw entries and sends them, in chunks, to a pool of worker threads
+/// which parse them. The parsed chunks are put back in input order, so `handle` is called on
+/// the current thread for each entry in the same order as it appears in `entries`.
+/// Entries which are dropped by the filters in `options` are not passed to `handle`.
+///
+/// At most `4 * threads` chunks are split ahead of the chunk that is being handled, which
+/// bounds memory usage even if some chunks take much longer to parse than others.
+///
+/// Stops at the first error returned by `handle` or encountered while reading `input`.
+pub fn parse_parallel<E, F>(
+    entries: E,
+    threads: usize,
+    options: &ParseOptions,
+    mut handle: F,
+) -> Result<(), Box<dyn Error>>
+where
+    E: Iterator<Item = io::Result<RawEntry>> + Send,
+    F: FnMut(&RawEntry, Result<UniparcEntry, ParseError>) -> Result<(), Box<dyn Error>>,
+{
+    let threads = threads.max(1);
+    let window = Window::new(threads * CHUNKS_PER_THREAD);
+    let (chunk_sender, chunk_receiver) = mpsc::sync_channel(threads * 2);
+    let (parsed_sender, parsed_receiver) = mpsc::sync_channel(threads * 2);
+
+    thread::scope(|scope| {
+        let window = &window;
+        scope.spawn(move || split_chunks(entries, chunk_sender, window));
+        // Only the workers hold on to the channels, so that they are closed once the workers stop.
+        let chunk_receiver = Arc::new(Mutex::new(chunk_receiver));
+        for _ in 0..threads {
+            let chunk_receiver = Arc::clone(&chunk_receiver);
+            let parsed_sender = parsed_sender.clone();
+            scope.spawn(move || parse_chunks(&chunk_receiver, &parsed_sender, options));
+        }
+        drop(chunk_receiver);
+        drop(parsed_sender);
+
+        let result = merge_chunks(parsed_receiver, window, &mut handle);
+        // Returning early drops the receiver of parsed chunks, which stops the worker threads,
+        // which in turn stops the splitter thread once it is no longer held back by the window.
+        window.advance(usize::MAX);
+        result
+    })
+}
+
+/// Pass the entries of the parsed chunks to `handle`, in the order of the chunks.
+fn merge_chunks<F>(
+    receiver: Receiver<ParsedChunk>,
+    window: &Window,
+    handle: &mut F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&RawEntry, Result<UniparcEntry, ParseError>) -> Result<(), Box<dyn Error>>,
+{
+    // Chunks which have been parsed ahead of the chunk that is to be handled next.
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for (index, chunk) in receiver {
+        pending.insert(index, chunk);
+        while let Some(chunk) = pending.remove(&next) {
+            for (raw_entry, result) in chunk? {
+                handle(&raw_entry, result)?;
+            }
+            next += 1;
+            window.advance(next);
+        }
+    }
+    Ok(())
+}
+
+/// Group `entries` into numbered chunks of raw entries.
+///
+/// Each chunk is only sent once it is inside `window`.
+fn split_chunks<E>(entries: E, sender: SyncSender<Chunk>, window: &Window)
+where
+    E: Iterator<Item = io::Result<RawEntry>>,
+{
+    let mut index = 0;
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+    for raw_entry in entries {
+        match raw_entry {
+            Ok(raw_entry) => chunk.push(raw_entry),
+            Err(error) => {
+                // Entries read before the error are still passed on, as when parsing sequentially.
+                if !chunk.is_empty() {
+                    window.wait(index);
+                    if sender.send((index, Ok(chunk))).is_err() {
+                        return;
+                    }
+                    index += 1;
+                }
+                window.wait(index);
+                let _ = sender.send((index, Err(error)));
+                return;
+            }
+        }
+        if chunk.len() == CHUNK_SIZE {
+            let full = std::mem::replace(&mut chunk, Vec::with_capacity(CHUNK_SIZE));
+            window.wait(index);
+            if sender.send((index, Ok(full))).is_err() {
+                return;
+            }
+            index += 1;
+        }
+    }
+    if !chunk.is_empty() {
+        window.wait(index);
+        let _ = sender.send((index, Ok(chunk)));
+    }
+}
+
+/// Parse chunks of raw entries until there are no more chunks or nobody is waiting for them.
+fn parse_chunks(
+    receiver: &Mutex<Receiver<Chunk>>,
+    sender: &SyncSender<ParsedChunk>,
+    options: &ParseOptions,
+) {
+    loop {
+        // The lock is released before parsing, so that other workers can receive chunks.
+        let message = receiver.lock().unwrap().recv();
+        let (index, chunk) = match message {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+        let parsed = chunk.map(|chunk| {
+            chunk
+                .into_iter()
+                .filter_map(|raw_entry| {
+                    let result = match parse_entry_ref(&raw_entry, options) {
+                        Ok(mut entry) => {
+                            if !filter_entry(&mut entry, options) {
+                                return None;
+                            }
+                            Ok(entry.into_owned())
+                        }
+                        Err(error) => Err(error),
+                    };
+                    Some((raw_entry, result))
+                })
+                .collect()
+        });
+        if sender.send((index, parsed)).is_err() {
+            return;
+        }
+    }
+}
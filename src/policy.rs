@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use error::ParseError;
+use splitter::RawEntry;
+
+/// What to do when an entry fails to parse.
+#[derive(Default)]
+pub enum ErrorPolicy {
+    /// Stop processing and return the error.
+    #[default]
+    Abort,
+    /// Log the error and continue with the next entry.
+    Skip,
+    /// Write the raw XML of the entry to a quarantine file and continue with the next entry.
+    Quarantine(Quarantine),
+}
+
+/// Destination for entries which failed to parse.
+pub struct Quarantine {
+    /// Raw XML of the rejected entries.
+    xml: Box<dyn Write>,
+    /// Position, UniParc ID and error message for each rejected entry.
+    reasons: Box<dyn Write>,
+}
+
+impl Quarantine {
+    pub fn new(xml: Box<dyn Write>, reasons: Box<dyn Write>) -> Quarantine {
+        Quarantine { xml, reasons }
+    }
+
+    /// Create `rejected.xml` and `rejected.tsv` in `basedir`.
+    pub fn create(basedir: &Path) -> io::Result<Quarantine> {
+        let xml = BufWriter::new(File::create(basedir.join("rejected.xml"))?);
+        let reasons = BufWriter::new(File::create(basedir.join("rejected.tsv"))?);
+        Ok(Quarantine::new(Box::new(xml), Box::new(reasons)))
+    }
+
+    /// Record an entry which failed to parse.
+    pub fn reject(&mut self, raw_entry: &RawEntry, error: &ParseError) -> io::Result<()> {
+        self.xml.write_all(&raw_entry.data)?;
+        self.xml.write_all(b"\n")?;
+        let reason = error.kind.to_string().replace(['\t', '\n'], " ");
+        writeln!(
+            self.reasons,
+            "{}\t{}\t{}\t{}",
+            raw_entry.offset, error.position, error.uniparc_id, reason
+        )
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.xml.flush()?;
+        self.reasons.flush()
+    }
+}
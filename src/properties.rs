@@ -1,8 +1,118 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::error::Error;
 
 use model::UniparcProperty;
 
+/// Name of the table for properties which are not mapped to a table of their own.
+pub const GENERIC_PROPERTY_TABLE: &str = "property";
+
+/// Names of the tables which do not hold properties.
+const ENTRY_TABLES: &[&str] = &["uniparc", "xref", "domain"];
+
+/// Property types which have a table of their own by default, with the name of the table.
+///
+/// The name of the table is also used as the name of the property in the output.
+const BUILTIN_PROPERTIES: &[(&str, &str)] = &[
+    ("NCBI_GI", "ncbi_gi"),
+    ("NCBI_taxonomy_id", "ncbi_taxonomy_id"),
+    ("protein_name", "protein_name"),
+    ("gene_name", "gene_name"),
+    ("chain", "pdb_chain"),
+    ("UniProtKB_accession", "uniprot_kb_accession"),
+    ("proteome_id", "proteome_id"),
+    ("component", "component"),
+];
+
+/// Name of a built-in property type in the output, e.g. `pdb_chain` for `chain`.
+pub fn builtin_property_name(property_type: &str) -> Option<&'static str> {
+    BUILTIN_PROPERTIES
+        .iter()
+        .find(|&&(builtin_type, _)| builtin_type == property_type)
+        .map(|&(_, name)| name)
+}
+
+/// Where to write properties whose type is not mapped to a table.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UnknownProperties {
+    /// Write them to the generic `property` table.
+    #[default]
+    Generic,
+    /// Write each type to a table of its own, called `property_<type>`.
+    PerType,
+}
+
+/// Mapping from the `type` attribute of `<property>` elements to output tables.
+///
+/// Built-in property types are written to their own tables unless they are mapped elsewhere.
+/// All other types are written according to `unknown`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertyMapping {
+    tables: HashMap<String, String>,
+    pub unknown: UnknownProperties,
+}
+
+impl PropertyMapping {
+    /// Write properties of type `property_type` to `table`.
+    ///
+    /// The table may be one of the built-in property tables or a new table, but not one of
+    /// the `uniparc`, `xref` or `domain` tables.
+    pub fn insert(&mut self, property_type: &str, table: &str) -> Result<(), Box<dyn Error>> {
+        let valid_name = !table.is_empty()
+            && table
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+        if !valid_name || ENTRY_TABLES.contains(&table) {
+            return Err(From::from(format!(
+                "table name '{}' is reserved or contains characters other than a-z, 0-9 and _",
+                table
+            )));
+        }
+        self.tables
+            .insert(property_type.to_string(), table.to_string());
+        Ok(())
+    }
+
+    /// Whether `name` is the name in the output of a built-in property or of a property type
+    /// which is mapped to a table.
+    pub fn is_known_property(&self, name: &str) -> bool {
+        BUILTIN_PROPERTIES
+            .iter()
+            .any(|&(_, builtin)| builtin == name)
+            || self.tables.keys().any(|property_type| {
+                builtin_property_name(property_type).unwrap_or(property_type) == name
+            })
+    }
+
+    /// Name of the table to which properties of type `property_type` are written.
+    pub fn table(&self, property_type: &str) -> Cow<'static, str> {
+        if let Some(table) = self.tables.get(property_type) {
+            return Cow::Owned(table.clone());
+        }
+        if let Some(name) = builtin_property_name(property_type) {
+            return Cow::Borrowed(name);
+        }
+        match self.unknown {
+            UnknownProperties::Generic => Cow::Borrowed(GENERIC_PROPERTY_TABLE),
+            UnknownProperties::PerType => {
+                let name = property_type
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() {
+                            c.to_ascii_lowercase()
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect::<String>();
+                Cow::Owned(format!("{}_{}", GENERIC_PROPERTY_TABLE, name))
+            }
+        }
+    }
+}
+
 /// Keep all properties for a given UniParc ID
+#[derive(Debug, Clone, PartialEq)]
 pub struct Properties<T> {
     pub component: T,
     pub gene_name: T,
@@ -12,6 +122,104 @@ pub struct Properties<T> {
     pub protein_name: T,
     pub proteome_id: T,
     pub uniprot_kb_accession: T,
+    /// Properties which are not mapped to a table of their own, in the `property` table.
+    pub generic: T,
+}
+
+impl<T> Properties<T> {
+    /// Create a `Properties` struct by calling `f` with the name of each property table.
+    pub fn from_fn<F: FnMut(&'static str) -> T>(mut f: F) -> Properties<T> {
+        Properties {
+            component: f("component"),
+            gene_name: f("gene_name"),
+            ncbi_gi: f("ncbi_gi"),
+            ncbi_taxonomy_id: f("ncbi_taxonomy_id"),
+            pdb_chain: f("pdb_chain"),
+            protein_name: f("protein_name"),
+            proteome_id: f("proteome_id"),
+            uniprot_kb_accession: f("uniprot_kb_accession"),
+            generic: f(GENERIC_PROPERTY_TABLE),
+        }
+    }
+
+    /// Like [`from_fn`](#method.from_fn), but stops at the first error returned by `f`.
+    pub fn try_from_fn<E, F>(mut f: F) -> Result<Properties<T>, E>
+    where
+        F: FnMut(&'static str) -> Result<T, E>,
+    {
+        Ok(Properties {
+            component: f("component")?,
+            gene_name: f("gene_name")?,
+            ncbi_gi: f("ncbi_gi")?,
+            ncbi_taxonomy_id: f("ncbi_taxonomy_id")?,
+            pdb_chain: f("pdb_chain")?,
+            protein_name: f("protein_name")?,
+            proteome_id: f("proteome_id")?,
+            uniprot_kb_accession: f("uniprot_kb_accession")?,
+            generic: f(GENERIC_PROPERTY_TABLE)?,
+        })
+    }
+
+    /// Apply `f` to the value of each property table.
+    pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Properties<U> {
+        Properties {
+            component: f(self.component),
+            gene_name: f(self.gene_name),
+            ncbi_gi: f(self.ncbi_gi),
+            ncbi_taxonomy_id: f(self.ncbi_taxonomy_id),
+            pdb_chain: f(self.pdb_chain),
+            protein_name: f(self.protein_name),
+            proteome_id: f(self.proteome_id),
+            uniprot_kb_accession: f(self.uniprot_kb_accession),
+            generic: f(self.generic),
+        }
+    }
+
+    /// Pairs of property table name and value.
+    pub fn named(&self) -> [(&'static str, &T); 9] {
+        [
+            ("component", &self.component),
+            ("gene_name", &self.gene_name),
+            ("ncbi_gi", &self.ncbi_gi),
+            ("ncbi_taxonomy_id", &self.ncbi_taxonomy_id),
+            ("pdb_chain", &self.pdb_chain),
+            ("protein_name", &self.protein_name),
+            ("proteome_id", &self.proteome_id),
+            ("uniprot_kb_accession", &self.uniprot_kb_accession),
+            (GENERIC_PROPERTY_TABLE, &self.generic),
+        ]
+    }
+
+    /// Value for the property table called `name`, if there is one.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut T> {
+        match name {
+            "component" => Some(&mut self.component),
+            "gene_name" => Some(&mut self.gene_name),
+            "ncbi_gi" => Some(&mut self.ncbi_gi),
+            "ncbi_taxonomy_id" => Some(&mut self.ncbi_taxonomy_id),
+            "pdb_chain" => Some(&mut self.pdb_chain),
+            "protein_name" => Some(&mut self.protein_name),
+            "proteome_id" => Some(&mut self.proteome_id),
+            "uniprot_kb_accession" => Some(&mut self.uniprot_kb_accession),
+            GENERIC_PROPERTY_TABLE => Some(&mut self.generic),
+            _ => None,
+        }
+    }
+
+    /// Pairs of property table name and mutable value.
+    pub fn named_mut(&mut self) -> [(&'static str, &mut T); 9] {
+        [
+            ("component", &mut self.component),
+            ("gene_name", &mut self.gene_name),
+            ("ncbi_gi", &mut self.ncbi_gi),
+            ("ncbi_taxonomy_id", &mut self.ncbi_taxonomy_id),
+            ("pdb_chain", &mut self.pdb_chain),
+            ("protein_name", &mut self.protein_name),
+            ("proteome_id", &mut self.proteome_id),
+            ("uniprot_kb_accession", &mut self.uniprot_kb_accession),
+            (GENERIC_PROPERTY_TABLE, &mut self.generic),
+        ]
+    }
 }
 
 impl Default for Properties<Vec<UniparcProperty>> {
@@ -25,6 +233,7 @@ impl Default for Properties<Vec<UniparcProperty>> {
             protein_name: Vec::new(),
             proteome_id: Vec::new(),
             uniprot_kb_accession: Vec::new(),
+            generic: Vec::new(),
         }
     }
 }
@@ -40,6 +249,7 @@ impl Default for Properties<HashMap<String, u64>> {
             protein_name: HashMap::new(),
             proteome_id: HashMap::new(),
             uniprot_kb_accession: HashMap::new(),
+            generic: HashMap::new(),
         }
     }
 }
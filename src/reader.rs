@@ -0,0 +1,802 @@
+use std::borrow::Cow;
+use std::io::BufRead;
+use std::str;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::reader::Reader;
+
+use checksum::{crc64_hex, md5_hex, refget, sha256_hex, SequenceDigests};
+use db_type::DbType;
+use error::{ParseError, ParseErrorKind};
+use filter::{filter_entry, DbFilter, DomainFilter, TaxonFilter};
+use model::{
+    UniparcDomainRef, UniparcEntry, UniparcEntryRef, UniparcPropertyRef, UniparcRef, UniparcXRefRef,
+};
+use properties::{builtin_property_name, Properties, PropertyMapping};
+use schema::SchemaOptions;
+use splitter::{EntrySplitter, RawEntry};
+use taxonomy::Taxonomy;
+
+fn invalid_value(element: &str, attribute: &Attribute) -> ParseErrorKind {
+    ParseErrorKind::InvalidValue {
+        element: element.to_string(),
+        attribute: String::from_utf8_lossy(attribute.key).into_owned(),
+        value: String::from_utf8_lossy(attribute.value).into_owned(),
+    }
+}
+
+fn unknown_attribute(element: &str, attribute: &Attribute) -> ParseErrorKind {
+    ParseErrorKind::UnknownAttribute {
+        element: element.to_string(),
+        attribute: String::from_utf8_lossy(attribute.key).into_owned(),
+    }
+}
+
+fn missing_attribute(element: &str, attribute: &str) -> ParseErrorKind {
+    ParseErrorKind::MissingAttribute {
+        element: element.to_string(),
+        attribute: attribute.to_string(),
+    }
+}
+
+fn unexpected_element(element: &str) -> ParseErrorKind {
+    ParseErrorKind::UnexpectedElement {
+        element: element.to_string(),
+    }
+}
+
+/// Location of the current tag or text in the raw XML of the entry.
+///
+/// The XML reader copies each event into its own buffer, so this is used to find the bytes
+/// of attribute values and texts in the raw XML, which outlives the buffer. The location is
+/// found using the positions of the reader in the raw XML, and does not depend on how the
+/// reader lays out its buffer.
+#[derive(Clone, Copy)]
+struct Source<'a> {
+    data: &'a [u8],
+    /// Position of the first byte of the tag or text in `data`.
+    start: usize,
+    /// Address of the first byte of the tag or text in the reader's buffer.
+    address: usize,
+}
+
+impl<'a> Source<'a> {
+    /// Source for `tag`, the contents of a start or empty tag which was read up to position
+    /// `end` of `data`.
+    ///
+    /// The contents start right after the last `<` before `end`, as attribute values cannot
+    /// contain a `<`. This holds for both `<tag ...>` and `<tag .../>`.
+    fn tag(data: &'a [u8], tag: &[u8], end: usize) -> Source<'a> {
+        let end = end.min(data.len());
+        let start = data[..end]
+            .iter()
+            .rposition(|&b| b == b'<')
+            .map_or(end, |lt| lt + 1);
+        Source {
+            data,
+            start,
+            address: tag.as_ptr() as usize,
+        }
+    }
+
+    /// Source for `text`, a trimmed text which was read starting at position `start` of `data`.
+    fn text(data: &'a [u8], text: &[u8], start: usize) -> Source<'a> {
+        let start = start.min(data.len());
+        let start = data[start..]
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .map_or(data.len(), |offset| start + offset);
+        Source {
+            data,
+            start,
+            address: text.as_ptr() as usize,
+        }
+    }
+
+    /// Find `bytes`, which must be part of the tag or text, in the raw XML.
+    fn borrow(&self, bytes: &[u8]) -> Option<&'a [u8]> {
+        let offset = (bytes.as_ptr() as usize).checked_sub(self.address)?;
+        let start = self.start.checked_add(offset)?;
+        let end = start.checked_add(bytes.len())?;
+        self.data.get(start..end).filter(|raw| *raw == bytes)
+    }
+}
+
+/// Collect the attributes of an element, failing on malformed attributes.
+fn collect_attributes<'b>(e: &'b BytesStart) -> Result<Vec<Attribute<'b>>, ParseErrorKind> {
+    e.attributes()
+        .map(|a| a.map_err(ParseErrorKind::Xml))
+        .collect()
+}
+
+/// Unescape and decode the value of an attribute.
+///
+/// The value is borrowed from the raw XML unless it contains escaped characters.
+fn attribute_value<'a>(
+    element: &str,
+    attribute: &Attribute,
+    source: &Source<'a>,
+) -> Result<Cow<'a, str>, ParseErrorKind> {
+    let value = attribute
+        .unescaped_value()
+        .map_err(|_| invalid_value(element, attribute))?;
+    let value = match value {
+        Cow::Borrowed(bytes) => match source.borrow(bytes) {
+            Some(raw) => str::from_utf8(raw).map(Cow::Borrowed),
+            None => str::from_utf8(bytes).map(|value| Cow::Owned(value.to_string())),
+        },
+        Cow::Owned(bytes) => String::from_utf8(bytes)
+            .map(Cow::Owned)
+            .map_err(|e| e.utf8_error()),
+    };
+    value.map_err(|_| invalid_value(element, attribute))
+}
+
+/// Parse the value of an attribute, e.g. as a number or an ISO-8601 date.
+fn parse_attribute<T: FromStr>(
+    element: &str,
+    attribute: &Attribute,
+    source: &Source,
+) -> Result<T, ParseErrorKind> {
+    attribute_value(element, attribute, source)?
+        .parse::<T>()
+        .map_err(|_| invalid_value(element, attribute))
+}
+
+/// Whether the length and CRC64 checksum of each sequence are verified.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SequenceCheck {
+    /// Use the `length` and `checksum` attributes as given.
+    #[default]
+    Off,
+    /// Log entries whose sequence does not match its attributes, but keep them.
+    Report,
+    /// Fail to parse entries whose sequence does not match its attributes.
+    Reject,
+}
+
+/// Options controlling which data is extracted from each entry.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Keep inactive cross-references (and their properties) instead of dropping them.
+    pub include_inactive: bool,
+    /// Tables to which the properties of cross-references are written.
+    pub properties: PropertyMapping,
+    /// Whether sequences are checked against their `length` and `checksum` attributes.
+    pub verify_sequences: SequenceCheck,
+    /// Digests which are computed for each sequence.
+    pub digests: SequenceDigests,
+    /// Only keep entries with domains of these databases or InterPro IDs.
+    pub domains: DomainFilter,
+    /// Databases whose cross-references are required, excluded or kept.
+    pub databases: DbFilter,
+    /// Only keep entries (or cross-references) of these NCBI taxa.
+    pub taxa: Option<TaxonFilter>,
+    /// Taxonomy used to look up the rank and lineage of `NCBI_taxonomy_id` properties, which
+    /// adds the `rank` and `lineage` columns to their table.
+    pub taxonomy: Option<Arc<Taxonomy>>,
+}
+
+impl ParseOptions {
+    /// Optional columns of the output tables which are filled with these options.
+    pub fn schema(&self) -> SchemaOptions {
+        SchemaOptions {
+            digests: self.digests,
+            taxonomy_table: self
+                .taxonomy
+                .as_ref()
+                .map(|_| self.properties.table("NCBI_taxonomy_id").into_owned()),
+        }
+    }
+}
+
+/// Add new data
+///
+/// Returns `true` if the cross-reference was kept.
+fn add_uniparc_xref<'a>(
+    uniparc_id: Cow<'a, str>,
+    uniparc_xrefs: &mut Vec<UniparcXRefRef<'a>>,
+    attributes: Vec<Attribute>,
+    source: &Source<'a>,
+    options: &ParseOptions,
+) -> Result<bool, ParseErrorKind> {
+    const ELEMENT: &str = "dbReference";
+    let mut db_type = None;
+    let mut db_id = Cow::Borrowed("");
+    let mut version_i = None;
+    let mut active = None;
+    let mut version = None;
+    let mut created = None;
+    let mut last = None;
+    for attribute in attributes {
+        match attribute.key {
+            b"type" => {
+                db_type = Some(DbType::from_name(&attribute_value(
+                    ELEMENT, &attribute, source,
+                )?));
+            }
+            b"id" => {
+                db_id = attribute_value(ELEMENT, &attribute, source)?;
+            }
+            b"version_i" => {
+                version_i = Some(parse_attribute(ELEMENT, &attribute, source)?);
+            }
+            b"active" => {
+                active = match &*attribute_value(ELEMENT, &attribute, source)? {
+                    "Y" => Some(true),
+                    "N" => Some(false),
+                    _ => return Err(invalid_value(ELEMENT, &attribute)),
+                };
+            }
+            b"version" => {
+                version = Some(parse_attribute(ELEMENT, &attribute, source)?);
+            }
+            b"created" => {
+                created = Some(parse_attribute(ELEMENT, &attribute, source)?);
+            }
+            b"last" => {
+                last = Some(parse_attribute(ELEMENT, &attribute, source)?);
+            }
+            _ => {
+                println!("Skipping attribute '{:?}' for dbReference.", attribute);
+            }
+        }
+    }
+    let active = active.ok_or_else(|| missing_attribute(ELEMENT, "active"))?;
+    if !active && !options.include_inactive {
+        return Ok(false);
+    }
+    let uniparc_xref = UniparcXRefRef {
+        uniparc_id,
+        xref_id: (uniparc_xrefs.len() + 1) as u64,
+        db_type: db_type.ok_or_else(|| missing_attribute(ELEMENT, "type"))?,
+        db_id,
+        version_i: version_i.ok_or_else(|| missing_attribute(ELEMENT, "version_i"))?,
+        active,
+        version,
+        created: created.ok_or_else(|| missing_attribute(ELEMENT, "created"))?,
+        last: last.ok_or_else(|| missing_attribute(ELEMENT, "last"))?,
+    };
+    uniparc_xrefs.push(uniparc_xref);
+    Ok(true)
+}
+
+fn add_property<'a>(
+    uniparc_id: Cow<'a, str>,
+    uniparc_xref: &UniparcXRefRef<'a>,
+    properties: &mut Properties<Vec<UniparcPropertyRef<'a>>>,
+    extra_properties: &mut Vec<UniparcPropertyRef<'a>>,
+    attributes: Vec<Attribute>,
+    source: &Source<'a>,
+    options: &ParseOptions,
+) -> Result<(), ParseErrorKind> {
+    const ELEMENT: &str = "property";
+    let mut attr_type = None;
+    let mut attr_value = None;
+    for attribute in attributes {
+        match attribute.key {
+            b"type" => attr_type = Some(attribute_value(ELEMENT, &attribute, source)?),
+            b"value" => attr_value = Some(attribute_value(ELEMENT, &attribute, source)?),
+            _ => {
+                println!("Skipping attribute '{:?}' for property.", attribute);
+            }
+        }
+    }
+    let attr_type = attr_type.ok_or_else(|| missing_attribute(ELEMENT, "type"))?;
+    let mut attr_value = attr_value.ok_or_else(|| missing_attribute(ELEMENT, "value"))?;
+
+    let xref_id = uniparc_xref.xref_id;
+
+    if attr_type == "chain" {
+        if uniparc_xref.db_type != DbType::Pdb {
+            return Err(unexpected_element(ELEMENT));
+        }
+        attr_value = Cow::Owned(format!("{}{}", uniparc_xref.db_id, attr_value));
+    }
+
+    let (mut rank, mut lineage) = (None, None);
+    if let (Some(taxonomy), "NCBI_taxonomy_id") = (options.taxonomy.as_ref(), &*attr_type) {
+        if let Ok(taxon) = attr_value.trim().parse() {
+            rank = taxonomy.shared_rank(taxon);
+            lineage = Some(taxonomy.lineage_names(taxon)).filter(|l| !l.is_empty());
+        }
+    }
+
+    let table = options.properties.table(&attr_type);
+    let property = UniparcPropertyRef {
+        uniparc_id,
+        xref_id,
+        property: match builtin_property_name(&attr_type) {
+            Some(name) => Cow::Borrowed(name),
+            None => attr_type,
+        },
+        value: attr_value,
+        rank,
+        lineage,
+        table: table.clone(),
+    };
+    match properties.get_mut(&table) {
+        Some(property_vec) => property_vec.push(property),
+        None => extra_properties.push(property),
+    }
+    Ok(())
+}
+
+fn add_signature_sequence_match<'a>(
+    uniparc_id: Cow<'a, str>,
+    uniparc_domains: &mut Vec<UniparcDomainRef<'a>>,
+    attributes: Vec<Attribute>,
+    source: &Source<'a>,
+) -> Result<(), ParseErrorKind> {
+    const ELEMENT: &str = "signatureSequenceMatch";
+    let mut database = Cow::Borrowed("");
+    let mut database_id = Cow::Borrowed("");
+    for attribute in attributes {
+        match attribute.key {
+            b"database" => {
+                database = attribute_value(ELEMENT, &attribute, source)?;
+            }
+            b"id" => {
+                database_id = attribute_value(ELEMENT, &attribute, source)?;
+            }
+            _ => return Err(unknown_attribute(ELEMENT, &attribute)),
+        }
+    }
+    let uniparc_domain = UniparcDomainRef {
+        uniparc_id,
+        database,
+        database_id,
+        ..Default::default()
+    };
+    uniparc_domains.push(uniparc_domain);
+    Ok(())
+}
+
+fn add_interpro_annotation<'a>(
+    uniparc_domains: &mut [UniparcDomainRef<'a>],
+    attributes: Vec<Attribute>,
+    source: &Source<'a>,
+) -> Result<(), ParseErrorKind> {
+    const ELEMENT: &str = "ipr";
+    let mut interpro_name = Cow::Borrowed("");
+    let mut interpro_id = Cow::Borrowed("");
+    for attribute in attributes {
+        match attribute.key {
+            b"name" => {
+                interpro_name = attribute_value(ELEMENT, &attribute, source)?;
+            }
+            b"id" => {
+                interpro_id = attribute_value(ELEMENT, &attribute, source)?;
+            }
+            _ => return Err(unknown_attribute(ELEMENT, &attribute)),
+        }
+    }
+    if interpro_name.is_empty() {
+        return Err(missing_attribute(ELEMENT, "name"));
+    }
+    if interpro_id.is_empty() {
+        return Err(missing_attribute(ELEMENT, "id"));
+    }
+    let uniparc_domain = uniparc_domains
+        .last_mut()
+        .ok_or_else(|| unexpected_element(ELEMENT))?;
+    if !uniparc_domain.interpro_name.is_empty() || !uniparc_domain.interpro_id.is_empty() {
+        return Err(unexpected_element(ELEMENT));
+    }
+    uniparc_domain.interpro_name = interpro_name;
+    uniparc_domain.interpro_id = interpro_id;
+    Ok(())
+}
+
+fn add_domain_definitions(
+    uniparc_domains: &mut Vec<UniparcDomainRef>,
+    attributes: Vec<Attribute>,
+    source: &Source,
+) -> Result<(), ParseErrorKind> {
+    const ELEMENT: &str = "lcn";
+    let mut domain_start: u32 = 0;
+    let mut domain_end: u32 = 0;
+    for attribute in attributes {
+        match attribute.key {
+            b"start" => domain_start = parse_attribute(ELEMENT, &attribute, source)?,
+            b"end" => domain_end = parse_attribute(ELEMENT, &attribute, source)?,
+            _ => return Err(unknown_attribute(ELEMENT, &attribute)),
+        }
+    }
+    if domain_start == 0 {
+        return Err(missing_attribute(ELEMENT, "start"));
+    }
+    if domain_end == 0 {
+        return Err(missing_attribute(ELEMENT, "end"));
+    }
+    let mut uniparc_domain = uniparc_domains
+        .pop()
+        .ok_or_else(|| unexpected_element(ELEMENT))?;
+    if (uniparc_domain.domain_start == 0) && (uniparc_domain.domain_end == 0) {
+        uniparc_domain.domain_start = domain_start;
+        uniparc_domain.domain_end = domain_end;
+        uniparc_domains.push(uniparc_domain);
+    } else {
+        let uniparc_domain_bak = uniparc_domain.clone();
+        uniparc_domain.domain_start = domain_start;
+        uniparc_domain.domain_end = domain_end;
+        uniparc_domains.push(uniparc_domain_bak);
+        uniparc_domains.push(uniparc_domain);
+    }
+    Ok(())
+}
+
+fn add_sequence<'a>(
+    uniparc: &mut UniparcRef<'a>,
+    attributes: Vec<Attribute>,
+    source: &Source<'a>,
+) -> Result<(), ParseErrorKind> {
+    const ELEMENT: &str = "sequence";
+    for attribute in attributes {
+        match attribute.key {
+            b"length" => {
+                uniparc.sequence_length = parse_attribute(ELEMENT, &attribute, source)?;
+            }
+            b"checksum" => {
+                uniparc.sequence_checksum = attribute_value(ELEMENT, &attribute, source)?;
+            }
+            _ => {
+                println!("Skipping attribute '{:?}' for sequence.", attribute);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compute the enabled digests of the sequence of `uniparc`.
+fn add_digests(uniparc: &mut UniparcRef, digests: SequenceDigests) {
+    let sequence = uniparc.sequence.as_bytes();
+    if digests.md5 {
+        uniparc.sequence_md5 = Some(Cow::Owned(md5_hex(sequence)));
+    }
+    if digests.sha256 {
+        uniparc.sequence_sha256 = Some(Cow::Owned(sha256_hex(sequence)));
+    }
+    if digests.refget {
+        uniparc.sequence_refget = Some(Cow::Owned(refget(sequence)));
+    }
+}
+
+/// Check that the sequence of `uniparc` matches its length and checksum attributes.
+fn verify_sequence(uniparc: &UniparcRef, check: SequenceCheck) -> Result<(), ParseErrorKind> {
+    if check == SequenceCheck::Off {
+        return Ok(());
+    }
+    let sequence = uniparc.sequence.as_bytes();
+    let mut error = None;
+    if sequence.len() != uniparc.sequence_length as usize {
+        error = Some(ParseErrorKind::SequenceLength {
+            expected: uniparc.sequence_length,
+            actual: sequence.len() as u32,
+        });
+    } else {
+        let checksum = crc64_hex(sequence);
+        if !checksum.eq_ignore_ascii_case(&uniparc.sequence_checksum) {
+            error = Some(ParseErrorKind::SequenceChecksum {
+                expected: uniparc.sequence_checksum.to_string(),
+                actual: checksum,
+            });
+        }
+    }
+    match error {
+        Some(error) if check == SequenceCheck::Reject => Err(error),
+        Some(error) => {
+            println!("Invalid sequence for '{}': {}.", uniparc.uniparc_id, error);
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+fn attribute_to_string(a: Attribute) -> (String, String) {
+    let key = String::from_utf8_lossy(a.key).into_owned();
+    let value = String::from_utf8_lossy(a.value).into_owned();
+    (key, value)
+}
+
+/// Text content of an element, which was read starting at position `start` of `data`,
+/// borrowed from the raw XML where possible.
+///
+/// Line breaks are removed from the text.
+fn text_value<'a>(
+    data: &'a [u8],
+    reader: &Reader<&'a [u8]>,
+    text: &BytesText,
+    start: usize,
+) -> Result<Cow<'a, str>, ParseErrorKind> {
+    if !text.contains(&b'&') && !text.contains(&b'\n') {
+        let source = Source::text(data, text, start);
+        if let Some(value) = source.borrow(text).and_then(|raw| str::from_utf8(raw).ok()) {
+            return Ok(Cow::Borrowed(value));
+        }
+    }
+    let value = text
+        .unescape_and_decode(reader)
+        .map_err(ParseErrorKind::Xml)?;
+    Ok(Cow::Owned(value.replace("\n", "")))
+}
+
+/// The `dbReference` element which encloses the current position in the entry.
+#[derive(Clone, Copy)]
+enum ParentXRef {
+    /// Not inside a `dbReference` element.
+    None,
+    /// Inside a `dbReference` element which was not kept, e.g. because it is inactive.
+    Skipped,
+    /// Inside the `dbReference` element stored at the given index of `xrefs`.
+    Kept(usize),
+}
+
+enum TextField {
+    Accession,
+    Sequence,
+}
+
+/// State machine which extracts data from the XML events of a single entry.
+struct EntryParser<'a, 'o> {
+    options: &'o ParseOptions,
+    data: &'a [u8],
+    reader: Reader<&'a [u8]>,
+    buf: Vec<u8>,
+    // Stack of elements that are currently open.
+    current_element: Vec<Vec<u8>>,
+    text_field: TextField,
+    parent_xref: ParentXRef,
+    entry: UniparcEntryRef<'a>,
+}
+
+impl<'a, 'o> EntryParser<'a, 'o> {
+    fn new(data: &'a [u8], options: &'o ParseOptions) -> EntryParser<'a, 'o> {
+        let mut reader = Reader::from_reader(data);
+        reader.trim_text(true);
+        EntryParser {
+            options,
+            data,
+            reader,
+            buf: Vec::new(),
+            current_element: Vec::new(),
+            text_field: TextField::Accession,
+            parent_xref: ParentXRef::None,
+            entry: Default::default(),
+        }
+    }
+
+    /// Process a single XML event.
+    ///
+    /// Returns `true` if the event completed the entry.
+    fn process_event(&mut self) -> Result<bool, ParseErrorKind> {
+        let entry = &mut self.entry;
+        // Position just past the previous event.
+        let start = self.reader.buffer_position();
+        let event = self
+            .reader
+            .read_event(&mut self.buf)
+            .map_err(ParseErrorKind::Xml)?;
+        // Position just past the event that was read.
+        let end = self.reader.buffer_position();
+        match event {
+            Event::Start(ref e) => {
+                let source = Source::tag(self.data, e, end);
+                match e.name() {
+                    // Entries cannot be nested, so an `entry` start tag inside an entry means
+                    // that the end tag of the entry is missing.
+                    b"entry" if !self.current_element.is_empty() => {
+                        return Err(unexpected_element("entry"))
+                    }
+                    b"entry" => {}
+                    b"dbReference" => {
+                        let kept = add_uniparc_xref(
+                            entry.uniparc.uniparc_id.clone(),
+                            &mut entry.xrefs,
+                            collect_attributes(e)?,
+                            &source,
+                            self.options,
+                        )?;
+                        self.parent_xref = if kept {
+                            ParentXRef::Kept(entry.xrefs.len() - 1)
+                        } else {
+                            ParentXRef::Skipped
+                        };
+                    }
+                    b"signatureSequenceMatch" => {
+                        add_signature_sequence_match(
+                            entry.uniparc.uniparc_id.clone(),
+                            &mut entry.domains,
+                            collect_attributes(e)?,
+                            &source,
+                        )?;
+                    }
+                    b"accession" => {
+                        self.text_field = TextField::Accession;
+                    }
+                    b"sequence" => {
+                        self.text_field = TextField::Sequence;
+                        add_sequence(&mut entry.uniparc, collect_attributes(e)?, &source)?;
+                    }
+                    _ => println!(
+                        "Skipping StartElement '{}' with attributes {:?}.",
+                        String::from_utf8_lossy(e.name()),
+                        collect_attributes(e)?
+                            .into_iter()
+                            .map(attribute_to_string)
+                            .collect::<Vec<_>>()
+                    ),
+                }
+                self.current_element.push(e.name().to_ascii_lowercase());
+            }
+            Event::Empty(ref e) => {
+                let source = Source::tag(self.data, e, end);
+                match e.name() {
+                    b"entry" if !self.current_element.is_empty() => {
+                        return Err(unexpected_element("entry"))
+                    }
+                    b"entry" => return Ok(true),
+                    b"dbReference" => {
+                        add_uniparc_xref(
+                            entry.uniparc.uniparc_id.clone(),
+                            &mut entry.xrefs,
+                            collect_attributes(e)?,
+                            &source,
+                            self.options,
+                        )?;
+                    }
+                    b"property" => match self.parent_xref {
+                        ParentXRef::Kept(index) => add_property(
+                            entry.uniparc.uniparc_id.clone(),
+                            &entry.xrefs[index],
+                            &mut entry.properties,
+                            &mut entry.extra_properties,
+                            collect_attributes(e)?,
+                            &source,
+                            self.options,
+                        )?,
+                        ParentXRef::Skipped => {}
+                        ParentXRef::None => return Err(unexpected_element("property")),
+                    },
+                    b"ipr" => add_interpro_annotation(
+                        &mut entry.domains,
+                        collect_attributes(e)?,
+                        &source,
+                    )?,
+                    b"lcn" => {
+                        add_domain_definitions(&mut entry.domains, collect_attributes(e)?, &source)?
+                    }
+                    _ => println!(
+                        "Skipping Empty element '{:?}' with attributes {:?}.",
+                        String::from_utf8_lossy(e.name()),
+                        collect_attributes(e)?
+                            .into_iter()
+                            .map(attribute_to_string)
+                            .collect::<Vec<_>>()
+                    ),
+                }
+            }
+            Event::Text(text) => {
+                let text = text_value(self.data, &self.reader, &text, start)?;
+                let entry = &mut self.entry;
+                match self.text_field {
+                    TextField::Accession => entry.uniparc.uniparc_id = text,
+                    TextField::Sequence => entry.uniparc.sequence = text,
+                }
+            }
+            Event::End(ref e) => {
+                // Mismatched end tags are reported by the XML reader itself.
+                self.current_element.pop();
+                match e.name() {
+                    b"entry" => return Ok(true),
+                    b"dbReference" => self.parent_xref = ParentXRef::None,
+                    b"sequence" => {
+                        let uniparc = &mut self.entry.uniparc;
+                        verify_sequence(uniparc, self.options.verify_sequences)?;
+                        add_digests(uniparc, self.options.digests);
+                    }
+                    _ => {}
+                }
+            }
+            Event::CData(e) => println!("Skipping CData '{:?}'.", e),
+            Event::Decl(e) => println!("Skipping Decl '{:?}'.", e),
+            Event::PI(e) => println!("Skipping PI '{:?}'.", e),
+            Event::Comment(comment) => println!("Skipping Comment: '{:?}'", comment),
+            Event::DocType(e) => println!("Skipping DocType: '{:?}'", e),
+            Event::Eof => return Err(ParseErrorKind::UnexpectedEof),
+        }
+        Ok(false)
+    }
+}
+
+/// Parse the raw XML of a single entry, borrowing from it wherever possible.
+pub fn parse_entry_ref<'a>(
+    raw_entry: &'a RawEntry,
+    options: &ParseOptions,
+) -> Result<UniparcEntryRef<'a>, ParseError> {
+    let mut parser = EntryParser::new(&raw_entry.data, options);
+    loop {
+        let result = parser.process_event();
+        parser.buf.clear();
+        match result {
+            Ok(true) => return Ok(parser.entry),
+            Ok(false) => {}
+            Err(kind) => {
+                return Err(ParseError {
+                    position: raw_entry.offset + parser.reader.buffer_position(),
+                    uniparc_id: parser.entry.uniparc.uniparc_id.into_owned(),
+                    kind,
+                })
+            }
+        }
+    }
+}
+
+/// Parse the raw XML of a single entry.
+pub fn parse_entry(
+    raw_entry: &RawEntry,
+    options: &ParseOptions,
+) -> Result<UniparcEntry, ParseError> {
+    parse_entry_ref(raw_entry, options).map(UniparcEntryRef::into_owned)
+}
+
+/// Iterator over the entries of a UniParc XML stream.
+///
+/// Each call to `next` reads the input up to and including the next `</entry>` tag,
+/// and returns everything that was extracted from that entry. Entries which are dropped by the
+/// filters in the `ParseOptions` are skipped.
+///
+/// Entries are parsed independently of one another, so iteration may continue after an entry
+/// fails to parse. Iteration stops after an I/O error.
+pub struct UniparcReader<R: BufRead> {
+    splitter: EntrySplitter<R>,
+    options: ParseOptions,
+}
+
+impl<R: BufRead> UniparcReader<R> {
+    pub fn new(input: R) -> UniparcReader<R> {
+        UniparcReader::with_options(input, ParseOptions::default())
+    }
+
+    pub fn with_options(input: R, options: ParseOptions) -> UniparcReader<R> {
+        UniparcReader {
+            splitter: EntrySplitter::new(input),
+            options,
+        }
+    }
+
+    /// Number of bytes consumed from the input so far.
+    pub fn buffer_position(&self) -> usize {
+        self.splitter.position()
+    }
+}
+
+impl<R: BufRead> Iterator for UniparcReader<R> {
+    type Item = Result<UniparcEntry, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let raw_entry = match self.splitter.next()? {
+                Ok(raw_entry) => raw_entry,
+                Err(error) => {
+                    return Some(Err(ParseError {
+                        position: self.splitter.position(),
+                        uniparc_id: String::new(),
+                        kind: ParseErrorKind::Io(error),
+                    }))
+                }
+            };
+            match parse_entry_ref(&raw_entry, &self.options) {
+                Ok(mut entry) => {
+                    if filter_entry(&mut entry, &self.options) {
+                        return Some(Ok(entry.into_owned()));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
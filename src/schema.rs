@@ -0,0 +1,272 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde_json::{self, Value};
+
+use checksum::SequenceDigests;
+use model::{Uniparc, UniparcDomain, UniparcProperty, UniparcXRef};
+use properties::Properties;
+
+/// Logical type of a column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    String,
+    Int32,
+    Int64,
+    Boolean,
+    /// Calendar date, written as `YYYY-MM-DD` to text formats.
+    Date,
+}
+
+impl ColumnType {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ColumnType::String => "string",
+            ColumnType::Int32 => "int32",
+            ColumnType::Int64 => "int64",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Date => "date",
+        }
+    }
+}
+
+/// Description of a single column of an output table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+impl Column {
+    fn new(name: &'static str, column_type: ColumnType) -> Column {
+        Column {
+            name,
+            column_type,
+            nullable: false,
+        }
+    }
+
+    fn nullable(name: &'static str, column_type: ColumnType) -> Column {
+        Column {
+            nullable: true,
+            ..Column::new(name, column_type)
+        }
+    }
+}
+
+/// Trait which means that a struct is stored as a row of an output table.
+pub trait Record {
+    /// Columns of the table, in the order in which they are written.
+    ///
+    /// These do not include optional columns, which are added after them depending on the
+    /// [`SchemaOptions`](struct.SchemaOptions.html).
+    fn columns() -> Vec<Column>;
+}
+
+impl Record for Uniparc {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("uniparc_id", ColumnType::String),
+            Column::new("sequence", ColumnType::String),
+            Column::new("sequence_length", ColumnType::Int32),
+            Column::new("sequence_checksum", ColumnType::String),
+        ]
+    }
+}
+
+impl Record for UniparcDomain {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("uniparc_id", ColumnType::String),
+            Column::new("database", ColumnType::String),
+            Column::new("database_id", ColumnType::String),
+            Column::new("interpro_name", ColumnType::String),
+            Column::new("interpro_id", ColumnType::String),
+            Column::new("domain_start", ColumnType::Int32),
+            Column::new("domain_end", ColumnType::Int32),
+        ]
+    }
+}
+
+impl Record for UniparcXRef {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("uniparc_id", ColumnType::String),
+            Column::new("xref_id", ColumnType::Int64),
+            Column::new("db_type", ColumnType::String),
+            Column::new("db_id", ColumnType::String),
+            Column::new("version_i", ColumnType::Int32),
+            Column::new("active", ColumnType::Boolean),
+            Column::nullable("version", ColumnType::Int32),
+            Column::new("created", ColumnType::Date),
+            Column::new("last", ColumnType::Date),
+        ]
+    }
+}
+
+impl Record for UniparcProperty {
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("uniparc_id", ColumnType::String),
+            Column::new("xref_id", ColumnType::Int64),
+            Column::new("property", ColumnType::String),
+            Column::new("value", ColumnType::String),
+        ]
+    }
+}
+
+/// Options which decide which optional columns the output tables have.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaOptions {
+    /// Digests which have a column in the `uniparc` table.
+    pub digests: SequenceDigests,
+    /// Property table with the `rank` and `lineage` columns, which is the table of the
+    /// `NCBI_taxonomy_id` properties if they are looked up in a taxonomy.
+    pub taxonomy_table: Option<String>,
+}
+
+impl SchemaOptions {
+    /// Columns of the `uniparc` table.
+    pub fn uniparc_columns(&self) -> Vec<Column> {
+        let mut columns = Uniparc::columns();
+        if self.digests.md5 {
+            columns.push(Column::nullable("sequence_md5", ColumnType::String));
+        }
+        if self.digests.sha256 {
+            columns.push(Column::nullable("sequence_sha256", ColumnType::String));
+        }
+        if self.digests.refget {
+            columns.push(Column::nullable("sequence_refget", ColumnType::String));
+        }
+        columns
+    }
+
+    /// Columns of the property table called `table`.
+    pub fn property_columns(&self, table: &str) -> Vec<Column> {
+        let mut columns = UniparcProperty::columns();
+        if self.has_taxonomy_columns(table) {
+            columns.push(Column::nullable("rank", ColumnType::String));
+            columns.push(Column::nullable("lineage", ColumnType::String));
+        }
+        columns
+    }
+
+    /// Whether the property table called `table` has the `rank` and `lineage` columns.
+    pub fn has_taxonomy_columns(&self, table: &str) -> bool {
+        self.taxonomy_table.as_deref() == Some(table)
+    }
+
+    /// Columns of the table called `name`.
+    pub fn table_columns(&self, name: &str) -> Vec<Column> {
+        match name {
+            "uniparc" => self.uniparc_columns(),
+            "domain" => UniparcDomain::columns(),
+            "xref" => UniparcXRef::columns(),
+            _ => self.property_columns(name),
+        }
+    }
+}
+
+/// Constraint linking columns of one table to the primary key of another table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKey {
+    pub columns: Vec<&'static str>,
+    pub table: &'static str,
+    pub references: Vec<&'static str>,
+}
+
+/// Description of an output table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    pub name: &'static str,
+    pub columns: Vec<Column>,
+    pub primary_key: Vec<&'static str>,
+    pub foreign_keys: Vec<ForeignKey>,
+}
+
+/// Schemas of all output tables.
+pub fn table_schemas(options: &SchemaOptions) -> Vec<TableSchema> {
+    let mut schemas = vec![
+        TableSchema {
+            name: "uniparc",
+            columns: options.uniparc_columns(),
+            primary_key: vec!["uniparc_id"],
+            foreign_keys: vec![],
+        },
+        TableSchema {
+            name: "domain",
+            columns: UniparcDomain::columns(),
+            primary_key: vec![],
+            foreign_keys: vec![ForeignKey {
+                columns: vec!["uniparc_id"],
+                table: "uniparc",
+                references: vec!["uniparc_id"],
+            }],
+        },
+        TableSchema {
+            name: "xref",
+            columns: UniparcXRef::columns(),
+            primary_key: vec!["uniparc_id", "xref_id"],
+            foreign_keys: vec![ForeignKey {
+                columns: vec!["uniparc_id"],
+                table: "uniparc",
+                references: vec!["uniparc_id"],
+            }],
+        },
+    ];
+    for &(name, _) in Properties::from_fn(|_| ()).named().iter() {
+        schemas.push(TableSchema {
+            name,
+            columns: options.property_columns(name),
+            primary_key: vec![],
+            foreign_keys: vec![ForeignKey {
+                columns: vec!["uniparc_id", "xref_id"],
+                table: "xref",
+                references: vec!["uniparc_id", "xref_id"],
+            }],
+        });
+    }
+    schemas
+}
+
+impl TableSchema {
+    fn to_json(&self) -> Value {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| json!({"name": c.name, "type": c.column_type.name(), "nullable": c.nullable}))
+            .collect::<Vec<_>>();
+        let foreign_keys = self
+            .foreign_keys
+            .iter()
+            .map(
+                |fk| json!({"columns": fk.columns, "table": fk.table, "references": fk.references}),
+            )
+            .collect::<Vec<_>>();
+        json!({
+            "name": self.name,
+            "columns": columns,
+            "primary_key": self.primary_key,
+            "foreign_keys": foreign_keys,
+        })
+    }
+}
+
+/// Write a JSON description of all output tables.
+pub fn write_schema_json<W: Write>(output: &mut W, options: &SchemaOptions) -> io::Result<()> {
+    let tables = table_schemas(options)
+        .iter()
+        .map(TableSchema::to_json)
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *output, &json!({ "tables": tables }))?;
+    writeln!(output)
+}
+
+/// Create `schema.json` in `basedir`.
+pub fn initialize_schema(basedir: &Path, options: &SchemaOptions) -> io::Result<()> {
+    let mut output = BufWriter::new(File::create(basedir.join("schema.json"))?);
+    write_schema_json(&mut output, options)?;
+    output.flush()
+}
@@ -0,0 +1,245 @@
+use std::error::Error;
+
+use model::{Uniparc, UniparcDomain, UniparcEntry, UniparcEntryRef, UniparcProperty, UniparcXRef};
+
+/// Destination for the rows extracted from each UniParc entry.
+///
+/// Each callback receives a single row of the corresponding table. Sinks can be combined
+/// by placing them in a tuple or a `Vec<Box<dyn Sink>>`, in which case every row is
+/// passed to each of the sinks in turn.
+pub trait Sink {
+    /// Handle a row of the `uniparc` table.
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>>;
+
+    /// Handle a row of the `xref` table.
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>>;
+
+    /// Handle a row of one of the property tables, as given by `property.table`.
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>>;
+
+    /// Handle a row of the `domain` table.
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>>;
+
+    /// Flush any buffered rows and finalize the outputs.
+    fn finish(&mut self) -> Result<(), Box<dyn Error>>;
+
+    /// Handle all rows extracted from a single entry.
+    ///
+    /// Rows are passed in the order uniparc, xrefs, properties, domains, so that
+    /// referenced rows are always seen before the rows which reference them.
+    fn on_entry(&mut self, entry: &UniparcEntry) -> Result<(), Box<dyn Error>> {
+        self.on_uniparc(&entry.uniparc)?;
+        for xref in &entry.xrefs {
+            self.on_xref(xref)?;
+        }
+        for &(_, properties) in entry.properties.named().iter() {
+            for property in properties {
+                self.on_property(property)?;
+            }
+        }
+        for property in &entry.extra_properties {
+            self.on_property(property)?;
+        }
+        for domain in &entry.domains {
+            self.on_domain(domain)?;
+        }
+        Ok(())
+    }
+
+    /// Handle all rows extracted from a single entry, borrowing from its raw XML.
+    ///
+    /// By default, the entry is copied and passed to `on_entry`. Sinks which can write
+    /// borrowed data directly should override this method to avoid the copy.
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        self.on_entry(&entry.clone().into_owned())
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        (**self).on_uniparc(uniparc)
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        (**self).on_xref(xref)
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        (**self).on_property(property)
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        (**self).on_domain(domain)
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        (**self).finish()
+    }
+
+    fn on_entry(&mut self, entry: &UniparcEntry) -> Result<(), Box<dyn Error>> {
+        (**self).on_entry(entry)
+    }
+
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        (**self).on_entry_ref(entry)
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for Box<S> {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        (**self).on_uniparc(uniparc)
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        (**self).on_xref(xref)
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        (**self).on_property(property)
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        (**self).on_domain(domain)
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        (**self).finish()
+    }
+
+    fn on_entry(&mut self, entry: &UniparcEntry) -> Result<(), Box<dyn Error>> {
+        (**self).on_entry(entry)
+    }
+
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        (**self).on_entry_ref(entry)
+    }
+}
+
+impl<S: Sink> Sink for Vec<S> {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.on_uniparc(uniparc)?;
+        }
+        Ok(())
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.on_xref(xref)?;
+        }
+        Ok(())
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.on_property(property)?;
+        }
+        Ok(())
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.on_domain(domain)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.finish()?;
+        }
+        Ok(())
+    }
+
+    fn on_entry(&mut self, entry: &UniparcEntry) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.on_entry(entry)?;
+        }
+        Ok(())
+    }
+
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        for sink in self.iter_mut() {
+            sink.on_entry_ref(entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Sink, B: Sink> Sink for (A, B) {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        self.0.on_uniparc(uniparc)?;
+        self.1.on_uniparc(uniparc)
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        self.0.on_xref(xref)?;
+        self.1.on_xref(xref)
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        self.0.on_property(property)?;
+        self.1.on_property(property)
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        self.0.on_domain(domain)?;
+        self.1.on_domain(domain)
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.0.finish()?;
+        self.1.finish()
+    }
+
+    fn on_entry(&mut self, entry: &UniparcEntry) -> Result<(), Box<dyn Error>> {
+        self.0.on_entry(entry)?;
+        self.1.on_entry(entry)
+    }
+
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        self.0.on_entry_ref(entry)?;
+        self.1.on_entry_ref(entry)
+    }
+}
+
+/// Sink which keeps all rows in memory.
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    pub uniparc: Vec<Uniparc>,
+    pub xrefs: Vec<UniparcXRef>,
+    pub properties: Vec<UniparcProperty>,
+    pub domains: Vec<UniparcDomain>,
+}
+
+impl MemorySink {
+    pub fn new() -> MemorySink {
+        MemorySink::default()
+    }
+}
+
+impl Sink for MemorySink {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        self.uniparc.push(uniparc.clone());
+        Ok(())
+    }
+
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        self.xrefs.push(xref.clone());
+        Ok(())
+    }
+
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        self.properties.push(property.clone());
+        Ok(())
+    }
+
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        self.domains.push(domain.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
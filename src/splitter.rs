@@ -0,0 +1,343 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+/// Raw XML of a single `<entry>` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawEntry {
+    /// Position of the `<entry>` start tag in the input stream, in bytes.
+    pub offset: usize,
+    /// Bytes from the `<entry>` start tag up to and including the `</entry>` end tag.
+    ///
+    /// If the entry is not closed before the input ends or the next entry starts, this contains
+    /// everything up to that point.
+    pub data: Vec<u8>,
+}
+
+/// Iterator which splits a UniParc XML stream into the raw XML of individual entries.
+///
+/// Only the markup required to find `<entry>` boundaries is inspected, so an entry containing
+/// malformed XML does not prevent the following entries from being read.
+pub struct EntrySplitter<R: BufRead> {
+    input: R,
+    position: usize,
+    finished: bool,
+    /// Position and start tag of an entry which started before the previous entry was closed.
+    pending: Option<(usize, Vec<u8>)>,
+}
+
+/// Whether `markup` is a start or end tag with the given name (e.g. `entry` or `/entry`).
+fn is_tag(markup: &[u8], name: &[u8]) -> bool {
+    let markup = &markup[1..];
+    markup.starts_with(name)
+        && match markup.get(name.len()) {
+            Some(b) => b.is_ascii_whitespace() || *b == b'>' || *b == b'/',
+            None => false,
+        }
+}
+
+/// Whether `markup` has been read up to its final `>`.
+fn is_complete(markup: &[u8]) -> bool {
+    if markup.starts_with(b"<!--") {
+        markup.len() >= 7 && markup.ends_with(b"-->")
+    } else if markup.starts_with(b"<![CDATA[") {
+        markup.len() >= 12 && markup.ends_with(b"]]>")
+    } else {
+        markup.ends_with(b">")
+    }
+}
+
+impl<R: BufRead> EntrySplitter<R> {
+    pub fn new(input: R) -> EntrySplitter<R> {
+        EntrySplitter {
+            input,
+            position: 0,
+            finished: false,
+            pending: None,
+        }
+    }
+
+    /// Number of bytes consumed from the input so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Consume input up to and including the next `byte`.
+    ///
+    /// Returns `false` if the end of input was reached first.
+    fn skip_until(&mut self, byte: u8) -> io::Result<bool> {
+        loop {
+            let (found, used) = {
+                let available = self.input.fill_buf()?;
+                if available.is_empty() {
+                    return Ok(false);
+                }
+                match available.iter().position(|&b| b == byte) {
+                    Some(i) => (true, i + 1),
+                    None => (false, available.len()),
+                }
+            };
+            self.input.consume(used);
+            self.position += used;
+            if found {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Read the remainder of a markup element whose `<` has already been pushed to `buf`.
+    fn read_markup(&mut self, buf: &mut Vec<u8>) -> io::Result<()> {
+        let start = buf.len() - 1;
+        let special = match self.input.fill_buf()?.first() {
+            Some(b) => *b == b'!' || *b == b'?',
+            None => return Ok(()),
+        };
+        if special {
+            // Comments, CDATA sections, declarations and processing instructions.
+            loop {
+                let n = self.input.read_until(b'>', buf)?;
+                self.position += n;
+                if n == 0 || is_complete(&buf[start..]) {
+                    return Ok(());
+                }
+            }
+        }
+        // Start and end tags, where `>` may also appear inside quoted attribute values.
+        let mut quote = None;
+        loop {
+            let (done, used) = {
+                let available = self.input.fill_buf()?;
+                if available.is_empty() {
+                    return Ok(());
+                }
+                let mut end = None;
+                for (i, &b) in available.iter().enumerate() {
+                    match quote {
+                        Some(q) if b == q => quote = None,
+                        Some(_) => {}
+                        None if b == b'"' || b == b'\'' => quote = Some(b),
+                        None if b == b'>' => {
+                            end = Some(i + 1);
+                            break;
+                        }
+                        None => {}
+                    }
+                }
+                let used = end.unwrap_or(available.len());
+                buf.extend_from_slice(&available[..used]);
+                (end.is_some(), used)
+            };
+            self.input.consume(used);
+            self.position += used;
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read up to and including the next `<entry>` start tag into `data`.
+    ///
+    /// Returns the position of the start tag, or `None` if the end of input was reached first.
+    fn read_start_tag(&mut self, data: &mut Vec<u8>) -> io::Result<Option<usize>> {
+        if let Some((offset, tag)) = self.pending.take() {
+            *data = tag;
+            return Ok(Some(offset));
+        }
+        loop {
+            if !self.skip_until(b'<')? {
+                return Ok(None);
+            }
+            let offset = self.position - 1;
+            data.clear();
+            data.push(b'<');
+            self.read_markup(data)?;
+            if is_tag(data, b"entry") {
+                return Ok(Some(offset));
+            }
+        }
+    }
+
+    fn read_entry(&mut self) -> io::Result<Option<RawEntry>> {
+        let mut data = Vec::new();
+        let offset = match self.read_start_tag(&mut data)? {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        if data.ends_with(b"/>") {
+            return Ok(Some(RawEntry { offset, data }));
+        }
+
+        // Read everything up to the matching `</entry>` end tag.
+        loop {
+            let n = self.input.read_until(b'<', &mut data)?;
+            self.position += n;
+            if n == 0 || !data.ends_with(b"<") {
+                break;
+            }
+            let start = data.len() - 1;
+            self.read_markup(&mut data)?;
+            if is_tag(&data[start..], b"/entry") {
+                break;
+            }
+            if is_tag(&data[start..], b"entry") {
+                // Entries cannot be nested, so the end tag of this entry is missing. The start
+                // tag is kept for the next entry, and this one is returned as it is.
+                let tag = data.split_off(start);
+                self.pending = Some((offset + start, tag));
+                break;
+            }
+        }
+        Ok(Some(RawEntry { offset, data }))
+    }
+}
+
+impl<R: BufRead> Iterator for EntrySplitter<R> {
+    type Item = io::Result<RawEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_entry() {
+            Ok(Some(raw_entry)) => Some(Ok(raw_entry)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Iterator which splits a sequence of UniParc XML streams into the raw XML of their entries.
+///
+/// Each stream is split on its own, so an entry which is not closed by the end of a stream is
+/// returned as it is, instead of being continued with the start of the next stream. Offsets
+/// are counted as if the streams were concatenated.
+pub struct InputSplitter<I: Iterator>
+where
+    I::Item: Read,
+{
+    inputs: I,
+    current: Option<EntrySplitter<BufReader<I::Item>>>,
+    // Number of bytes in the streams before the current one.
+    offset: usize,
+    finished: bool,
+}
+
+impl<I: Iterator> InputSplitter<I>
+where
+    I::Item: Read,
+{
+    pub fn new<T: IntoIterator<IntoIter = I>>(inputs: T) -> InputSplitter<I> {
+        InputSplitter {
+            inputs: inputs.into_iter(),
+            current: None,
+            offset: 0,
+            finished: false,
+        }
+    }
+
+    /// Number of bytes consumed from all streams so far.
+    pub fn position(&self) -> usize {
+        self.offset + self.current.as_ref().map_or(0, EntrySplitter::position)
+    }
+}
+
+impl<I: Iterator> Iterator for InputSplitter<I>
+where
+    I::Item: Read,
+{
+    type Item = io::Result<RawEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            if let Some(ref mut splitter) = self.current {
+                match splitter.next() {
+                    Some(Ok(mut raw_entry)) => {
+                        raw_entry.offset += self.offset;
+                        return Some(Ok(raw_entry));
+                    }
+                    Some(Err(error)) => {
+                        // As with a single stream, iteration stops after an I/O error.
+                        self.finished = true;
+                        return Some(Err(error));
+                    }
+                    None => self.offset += EntrySplitter::position(splitter),
+                }
+            }
+            match self.inputs.next() {
+                Some(input) => self.current = Some(EntrySplitter::new(BufReader::new(input))),
+                None => {
+                    self.current = None;
+                    self.finished = true;
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(data: &[u8]) -> Vec<RawEntry> {
+        EntrySplitter::new(data)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn splits_entries() {
+        let data = b"<?xml version=\"1.0\"?>\n<uniparc>\n<entry a=\"1\">x</entry>\n<entry>y</entry>\n</uniparc>\n";
+        let entries = split(data);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].data, b"<entry a=\"1\">x</entry>".to_vec());
+        assert_eq!(entries[1].data, b"<entry>y</entry>".to_vec());
+        assert_eq!(&data[entries[1].offset..entries[1].offset + 7], b"<entry>");
+    }
+
+    #[test]
+    fn ignores_markup_inside_quotes_and_comments() {
+        let data =
+            b"<!-- <entry> --><entry><p v=\"</entry>\"/><!-- </entry> --></entry><entryset/>";
+        let entries = split(data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].data,
+            b"<entry><p v=\"</entry>\"/><!-- </entry> --></entry>".to_vec()
+        );
+    }
+
+    #[test]
+    fn returns_truncated_entry() {
+        let entries = split(b"<uniparc><entry><accession>UPI");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].data, b"<entry><accession>UPI".to_vec());
+    }
+
+    #[test]
+    fn ends_unclosed_entry_at_next_entry() {
+        let data = b"<uniparc><entry>x<entry a=\"1\">y</entry><entry/></uniparc>";
+        let entries = split(data);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].data, b"<entry>x".to_vec());
+        assert_eq!(entries[1].data, b"<entry a=\"1\">y</entry>".to_vec());
+        assert_eq!(entries[2].data, b"<entry/>".to_vec());
+        assert_eq!(&data[entries[1].offset..entries[1].offset + 8], b"<entry a");
+    }
+
+    #[test]
+    fn splits_inputs_separately() {
+        let inputs: Vec<&[u8]> = vec![b"<uniparc><entry>x", b"<uniparc><entry>y</entry></uniparc>"];
+        let entries = InputSplitter::new(inputs)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].data, b"<entry>x".to_vec());
+        assert_eq!(entries[1].data, b"<entry>y</entry>".to_vec());
+        assert_eq!(entries[1].offset, 26);
+    }
+}
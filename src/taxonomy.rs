@@ -0,0 +1,203 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// ID of the root of the NCBI taxonomy, which is its own parent.
+const ROOT: u32 = 1;
+
+#[derive(Debug, Clone)]
+struct Node {
+    parent: u32,
+    /// Rank of the taxon, shared by all taxa with the same rank.
+    rank: Arc<str>,
+    name: String,
+    /// Scientific names of the lineage, built the first time they are needed.
+    lineage: OnceLock<Arc<str>>,
+}
+
+/// NCBI taxonomy, as read from the `nodes.dmp` and `names.dmp` files of a taxdump.
+#[derive(Clone, Default)]
+pub struct Taxonomy {
+    nodes: HashMap<u32, Node>,
+}
+
+impl fmt::Debug for Taxonomy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Taxonomy {{ {} taxa }}", self.nodes.len())
+    }
+}
+
+/// Fields of a line of a `.dmp` file, which are separated by `\t|\t` and end with `\t|`.
+fn dmp_fields(line: &str) -> Vec<&str> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let line = line.strip_suffix("\t|").unwrap_or(line);
+    line.split("\t|\t").collect()
+}
+
+fn parse_taxon(value: &str, file: &str) -> Result<u32, Box<dyn Error>> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| From::from(format!("invalid taxonomy ID '{}' in {}", value, file)))
+}
+
+impl Taxonomy {
+    /// Read the taxonomy from the `nodes.dmp` and `names.dmp` files in `taxdump_dir`.
+    pub fn load(taxdump_dir: &Path) -> Result<Taxonomy, Box<dyn Error>> {
+        let nodes = BufReader::new(File::open(taxdump_dir.join("nodes.dmp"))?);
+        let names = BufReader::new(File::open(taxdump_dir.join("names.dmp"))?);
+        Taxonomy::read(nodes, names)
+    }
+
+    /// Read the taxonomy from the contents of `nodes.dmp` and `names.dmp`.
+    ///
+    /// Only the scientific name of each taxon is kept.
+    pub fn read<N: BufRead, M: BufRead>(nodes: N, names: M) -> Result<Taxonomy, Box<dyn Error>> {
+        let mut taxonomy = Taxonomy::default();
+        let mut ranks: HashMap<String, Arc<str>> = HashMap::new();
+        for line in nodes.lines() {
+            let line = line?;
+            let fields = dmp_fields(&line);
+            if fields.len() < 3 {
+                return Err(From::from(format!("invalid line in nodes.dmp: '{}'", line)));
+            }
+            let rank = ranks
+                .entry(fields[2].to_string())
+                .or_insert_with(|| Arc::from(fields[2]));
+            let node = Node {
+                parent: parse_taxon(fields[1], "nodes.dmp")?,
+                rank: Arc::clone(rank),
+                name: String::new(),
+                lineage: OnceLock::new(),
+            };
+            taxonomy
+                .nodes
+                .insert(parse_taxon(fields[0], "nodes.dmp")?, node);
+        }
+        for line in names.lines() {
+            let line = line?;
+            let fields = dmp_fields(&line);
+            if fields.len() < 4 {
+                return Err(From::from(format!("invalid line in names.dmp: '{}'", line)));
+            }
+            if fields[3] != "scientific name" {
+                continue;
+            }
+            let taxon = parse_taxon(fields[0], "names.dmp")?;
+            if let Some(node) = taxonomy.nodes.get_mut(&taxon) {
+                node.name = fields[1].to_string();
+            }
+        }
+        Ok(taxonomy)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, taxon: u32) -> bool {
+        self.nodes.contains_key(&taxon)
+    }
+
+    pub fn parent(&self, taxon: u32) -> Option<u32> {
+        self.nodes.get(&taxon).map(|node| node.parent)
+    }
+
+    /// Rank of the taxon, e.g. `species` or `no rank`.
+    pub fn rank(&self, taxon: u32) -> Option<&str> {
+        self.nodes.get(&taxon).map(|node| &node.rank[..])
+    }
+
+    /// Rank of the taxon, which can be cloned without copying it.
+    pub fn shared_rank(&self, taxon: u32) -> Option<Arc<str>> {
+        self.nodes.get(&taxon).map(|node| Arc::clone(&node.rank))
+    }
+
+    /// Scientific name of the taxon.
+    pub fn name(&self, taxon: u32) -> Option<&str> {
+        self.nodes.get(&taxon).map(|node| &node.name[..])
+    }
+
+    /// ID of the taxon with the scientific name `name`, e.g. `Mammalia`.
+    pub fn find(&self, name: &str) -> Option<u32> {
+        self.nodes
+            .iter()
+            .filter(|&(_, node)| node.name == name)
+            .map(|(&taxon, _)| taxon)
+            .min()
+    }
+
+    /// IDs of the ancestors of the taxon, from the root (excluded) down to the taxon itself.
+    ///
+    /// Returns an empty lineage for taxa which are not in the taxonomy.
+    pub fn lineage(&self, taxon: u32) -> Vec<u32> {
+        let mut lineage = Vec::new();
+        let mut current = taxon;
+        while current != ROOT {
+            let node = match self.nodes.get(&current) {
+                Some(node) => node,
+                None => break,
+            };
+            lineage.push(current);
+            // Guard against cycles in a malformed taxdump.
+            if node.parent == current || lineage.len() > self.nodes.len() {
+                break;
+            }
+            current = node.parent;
+        }
+        lineage.reverse();
+        lineage
+    }
+
+    /// Scientific names of the lineage of the taxon, joined with `; `.
+    ///
+    /// The names are only joined the first time the lineage of a taxon (or of one of its
+    /// descendants) is requested, and shared afterwards.
+    pub fn lineage_names(&self, taxon: u32) -> Arc<str> {
+        if let Some(lineage) = self.nodes.get(&taxon).and_then(|node| node.lineage.get()) {
+            return Arc::clone(lineage);
+        }
+        // Build and keep the lineages of all ancestors whose lineage is not known yet, from the
+        // root down to the taxon itself.
+        let mut names: Arc<str> = Arc::from("");
+        for (i, ancestor) in self.lineage(taxon).into_iter().enumerate() {
+            let node = &self.nodes[&ancestor];
+            names = Arc::clone(node.lineage.get_or_init(|| {
+                if i == 0 {
+                    Arc::from(&node.name[..])
+                } else {
+                    Arc::from(format!("{}; {}", names, node.name))
+                }
+            }));
+        }
+        names
+    }
+
+    /// The given taxa together with all of their descendants.
+    pub fn descendants<I: IntoIterator<Item = u32>>(&self, ancestors: I) -> HashSet<u32> {
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (&taxon, node) in &self.nodes {
+            if taxon != node.parent {
+                children.entry(node.parent).or_default().push(taxon);
+            }
+        }
+        let mut taxa = HashSet::new();
+        let mut stack = ancestors.into_iter().collect::<Vec<_>>();
+        while let Some(taxon) = stack.pop() {
+            if taxa.insert(taxon) {
+                if let Some(children) = children.get(&taxon) {
+                    stack.extend(children);
+                }
+            }
+        }
+        taxa
+    }
+}
@@ -1,15 +1,49 @@
+use std::collections::HashMap;
+use std::error::Error;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
 
 use flate2::write::GzEncoder;
 use flate2::{Compression, GzBuilder};
 
-use model::{Uniparc, UniparcDomain, UniparcProperty, UniparcXRef};
+use csv_format::CsvFormat;
+use model::{
+    Uniparc, UniparcDomain, UniparcDomainRef, UniparcEntryRef, UniparcProperty, UniparcPropertyRef,
+    UniparcRef, UniparcXRef, UniparcXRefRef,
+};
 use properties::Properties;
+use schema::SchemaOptions;
+use sink::Sink;
 
-/// Struct for keeping buffers to output files.
-pub struct OutputBuffers<T> {
+/// Output of a table written by a `TsvSink`.
+pub trait TsvOutput: Write {
+    /// Write everything that is still buffered once all rows have been written, including
+    /// e.g. the trailer of a compressed file.
+    fn finalize(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl<W: Write> TsvOutput for BufWriter<W> {}
+
+impl<W: Write> TsvOutput for GzEncoder<W> {
+    fn finalize(&mut self) -> io::Result<()> {
+        self.try_finish()
+    }
+}
+
+impl TsvOutput for File {}
+
+impl TsvOutput for Vec<u8> {}
+
+impl TsvOutput for io::Sink {}
+
+/// Function which creates the output for a table, given its name.
+type CreateOutput<T> = dyn FnMut(&str) -> io::Result<T>;
+
+/// Sink which writes each table to a separate CSV file.
+pub struct TsvSink<T> {
     /// The base file which contains all uniparc sequences
     uniparc: T,
     /// File containing domain definitions for UniParc sequences
@@ -17,181 +51,308 @@ pub struct OutputBuffers<T> {
     /// File containing all uniparc cross-references
     xref: T,
     /// Files for each of the uniparc cross-references.
-    component: T,
-    gene_name: T,
-    ncbi_gi: T,
-    ncbi_taxonomy_id: T,
-    pdb_chain: T,
-    protein_name: T,
-    proteome_id: T,
-    uniprot_kb_accession: T,
+    properties: Properties<T>,
+    /// Files for property tables other than the built-in ones, created as they are needed.
+    extra_properties: HashMap<String, T>,
+    /// Creates the output for a table, given its name.
+    create: Box<CreateOutput<T>>,
+    /// Dialect used for all output files.
+    format: CsvFormat,
+    /// Optional columns of the tables.
+    schema: SchemaOptions,
 }
 
-/// Initialize all output buffers
-pub fn initialize_outputs(basedir: PathBuf) -> OutputBuffers<BufWriter<File>> {
-    let create_outfile = |filename: &str| {
-        let file = File::create(basedir.join(filename)).unwrap();
-        BufWriter::new(file)
-    };
+impl<T: Write> TsvSink<T> {
+    /// Create a sink which writes each table to the output returned by `create` for its name,
+    /// with the optional columns given by `schema`.
+    ///
+    /// Outputs for property tables other than the built-in ones are only created when the
+    /// first row is written to them, so errors from `create` can also be returned while
+    /// writing.
+    pub fn new<F>(mut create: F, format: CsvFormat, schema: SchemaOptions) -> io::Result<TsvSink<T>>
+    where
+        F: FnMut(&str) -> io::Result<T> + 'static,
+    {
+        Ok(TsvSink {
+            uniparc: create("uniparc")?,
+            domain: create("domain")?,
+            xref: create("xref")?,
+            properties: Properties::try_from_fn(&mut create)?,
+            extra_properties: HashMap::new(),
+            create: Box::new(create),
+            format,
+            schema,
+        })
+    }
+}
 
-    OutputBuffers {
-        uniparc: create_outfile("uniparc.tsv"),
-        domain: create_outfile("domain.tsv"),
-        xref: create_outfile("xref.tsv"),
-        component: create_outfile("component.tsv"),
-        gene_name: create_outfile("gene_name.tsv"),
-        ncbi_gi: create_outfile("ncbi_gi.tsv"),
-        ncbi_taxonomy_id: create_outfile("ncbi_taxonomy_id.tsv"),
-        pdb_chain: create_outfile("pdb_chain.tsv"),
-        protein_name: create_outfile("protein_name.tsv"),
-        proteome_id: create_outfile("proteome_id.tsv"),
-        uniprot_kb_accession: create_outfile("uniprot_kb_accession.tsv"),
+/// Output for the property table called `table`, which is created if it does not exist yet.
+fn property_output<'s, T>(
+    properties: &'s mut Properties<T>,
+    extra_properties: &'s mut HashMap<String, T>,
+    create: &mut CreateOutput<T>,
+    table: &str,
+) -> io::Result<&'s mut T> {
+    if let Some(output) = properties.get_mut(table) {
+        return Ok(output);
     }
+    if !extra_properties.contains_key(table) {
+        extra_properties.insert(table.to_string(), create(table)?);
+    }
+    Ok(extra_properties.get_mut(table).unwrap())
+}
+
+/// Initialize all output buffers
+pub fn initialize_outputs(
+    basedir: PathBuf,
+    format: CsvFormat,
+    schema: SchemaOptions,
+) -> Result<TsvSink<BufWriter<File>>, Box<dyn Error>> {
+    format.validate()?;
+    let header = format.clone();
+    let columns = schema.clone();
+    let create_outfile = move |name: &str| {
+        let file = File::create(basedir.join(format!("{}.tsv", name)))?;
+        let mut output = BufWriter::new(file);
+        if header.header {
+            header.write_header(&mut output, &columns.table_columns(name))?;
+        }
+        Ok(output)
+    };
+    Ok(TsvSink::new(create_outfile, format, schema)?)
 }
 
 // Initialize compressed output buffers
-pub fn initialize_outputs_compressed(basedir: PathBuf) -> OutputBuffers<GzEncoder<File>> {
-    let create_outfile = |filename: &str| {
-        let f = File::create(basedir.join(format!("{}{}", filename, ".gz"))).unwrap();
-        GzBuilder::new()
+pub fn initialize_outputs_compressed(
+    basedir: PathBuf,
+    format: CsvFormat,
+    schema: SchemaOptions,
+) -> Result<TsvSink<GzEncoder<File>>, Box<dyn Error>> {
+    format.validate()?;
+    let header = format.clone();
+    let columns = schema.clone();
+    let create_outfile = move |name: &str| {
+        let filename = format!("{}.tsv", name);
+        let f = File::create(basedir.join(format!("{}{}", filename, ".gz")))?;
+        let mut output = GzBuilder::new()
             .filename(filename)
-            .write(f, Compression::default())
+            .write(f, Compression::default());
+        if header.header {
+            header.write_header(&mut output, &columns.table_columns(name))?;
+        }
+        Ok(output)
     };
-
-    OutputBuffers {
-        uniparc: create_outfile("uniparc.tsv"),
-        domain: create_outfile("domain.tsv"),
-        xref: create_outfile("xref.tsv"),
-        component: create_outfile("component.tsv"),
-        gene_name: create_outfile("gene_name.tsv"),
-        ncbi_gi: create_outfile("ncbi_gi.tsv"),
-        ncbi_taxonomy_id: create_outfile("ncbi_taxonomy_id.tsv"),
-        pdb_chain: create_outfile("pdb_chain.tsv"),
-        protein_name: create_outfile("protein_name.tsv"),
-        proteome_id: create_outfile("proteome_id.tsv"),
-        uniprot_kb_accession: create_outfile("uniprot_kb_accession.tsv"),
-    }
+    Ok(TsvSink::new(create_outfile, format, schema)?)
 }
 
 /// Trait which means that a struct can be serialized to CSV format.
 pub trait Writable {
-    fn to_csv<T: Write>(&self, output: &mut T);
+    /// Write the record, including the optional columns of its table given by `schema`.
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()>;
 }
 
 impl Writable for Uniparc {
-    fn to_csv<T: Write>(&self, output: &mut T) {
-        write!(
-            output,
-            "{:?}\t{:?}\t{:?}\t{:?}\n",
-            self.uniparc_id, self.sequence, self.sequence_length, self.sequence_checksum
-        )
-        .unwrap();
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()> {
+        self.as_ref().to_csv(output, format, schema)
     }
 }
 
 impl Writable for UniparcDomain {
-    fn to_csv<T: Write>(&self, output: &mut T) {
-        write!(
-            output,
-            "{:?}\t{:?}\t{:?}\t{:?}\t{:?}\t{:?}\t{:?}\n",
-            self.uniparc_id,
-            self.database,
-            self.database_id,
-            self.interpro_name,
-            self.interpro_id,
-            self.domain_start,
-            self.domain_end,
-        )
-        .unwrap();
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()> {
+        self.as_ref().to_csv(output, format, schema)
     }
 }
 
 impl Writable for UniparcXRef {
-    fn to_csv<T: Write>(&self, output: &mut T) {
-        write!(
-            output,
-            "{:?}\t{:?}\t{:?}\t{:?}\t{:?}\t{:?}\t{:?}\t{:?}\t{:?}\n",
-            self.uniparc_id,
-            self.xref_id,
-            self.db_type,
-            self.db_id,
-            self.version_i,
-            self.active,
-            self.version,
-            self.created,
-            self.last
-        )
-        .unwrap();
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()> {
+        self.as_ref().to_csv(output, format, schema)
     }
 }
 
 impl Writable for UniparcProperty {
-    fn to_csv<T: Write>(&self, output: &mut T) {
-        write!(
-            output,
-            "{:?}\t{:?}\t{:?}\t{:?}\n",
-            self.uniparc_id, self.xref_id, self.property, self.value,
-        )
-        .unwrap();
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()> {
+        self.as_ref().to_csv(output, format, schema)
     }
 }
 
-// Writers
-pub fn write_uniparc<T: Write>(outputs: &mut OutputBuffers<T>, uniparc: &Uniparc) {
-    uniparc.to_csv(&mut outputs.uniparc);
-}
-
-pub fn write_uniparc_domains<T: Write>(
-    outputs: &mut OutputBuffers<T>,
-    uniparc_domains: &Vec<UniparcDomain>,
-) {
-    for uniparc_domain in uniparc_domains {
-        uniparc_domain.to_csv(&mut outputs.domain);
+impl<'a> Writable for UniparcRef<'a> {
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()> {
+        let mut record = format.record(output);
+        record.string(&self.uniparc_id)?;
+        record.string(&self.sequence)?;
+        record.number(self.sequence_length)?;
+        record.string(&self.sequence_checksum)?;
+        if schema.digests.md5 {
+            record.optional_string(self.sequence_md5.as_deref())?;
+        }
+        if schema.digests.sha256 {
+            record.optional_string(self.sequence_sha256.as_deref())?;
+        }
+        if schema.digests.refget {
+            record.optional_string(self.sequence_refget.as_deref())?;
+        }
+        record.finish()
     }
 }
 
-pub fn write_uniparc_xrefs<T: Write>(
-    outputs: &mut OutputBuffers<T>,
-    uniparc_xrefs: &Vec<UniparcXRef>,
-) {
-    for uniparc_xref in uniparc_xrefs {
-        uniparc_xref.to_csv(&mut outputs.xref);
+impl<'a> Writable for UniparcDomainRef<'a> {
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        _: &SchemaOptions,
+    ) -> io::Result<()> {
+        let mut record = format.record(output);
+        record.string(&self.uniparc_id)?;
+        record.string(&self.database)?;
+        record.string(&self.database_id)?;
+        record.string(&self.interpro_name)?;
+        record.string(&self.interpro_id)?;
+        record.number(self.domain_start)?;
+        record.number(self.domain_end)?;
+        record.finish()
     }
 }
 
-pub fn write_uniparc_properties<T: Write>(
-    headers: &mut OutputBuffers<T>,
-    properties: &Properties<Vec<UniparcProperty>>,
-) {
-    for prop in properties.component.iter() {
-        prop.to_csv(&mut headers.component);
+impl<'a> Writable for UniparcXRefRef<'a> {
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        _: &SchemaOptions,
+    ) -> io::Result<()> {
+        let mut record = format.record(output);
+        record.string(&self.uniparc_id)?;
+        record.number(self.xref_id)?;
+        record.string(self.db_type.name())?;
+        record.string(&self.db_id)?;
+        record.number(self.version_i)?;
+        record.boolean(self.active)?;
+        record.optional_number(self.version)?;
+        record.date(self.created)?;
+        record.date(self.last)?;
+        record.finish()
     }
+}
 
-    for prop in properties.gene_name.iter() {
-        prop.to_csv(&mut headers.gene_name);
+impl<'a> Writable for UniparcPropertyRef<'a> {
+    fn to_csv<T: Write>(
+        &self,
+        output: &mut T,
+        format: &CsvFormat,
+        schema: &SchemaOptions,
+    ) -> io::Result<()> {
+        let mut record = format.record(output);
+        record.string(&self.uniparc_id)?;
+        record.number(self.xref_id)?;
+        record.string(&self.property)?;
+        record.string(&self.value)?;
+        if schema.has_taxonomy_columns(&self.table) {
+            record.optional_string(self.rank.as_deref())?;
+            record.optional_string(self.lineage.as_deref())?;
+        }
+        record.finish()
     }
+}
 
-    for prop in properties.ncbi_gi.iter() {
-        prop.to_csv(&mut headers.ncbi_gi);
+impl<T: TsvOutput> Sink for TsvSink<T> {
+    fn on_uniparc(&mut self, uniparc: &Uniparc) -> Result<(), Box<dyn Error>> {
+        uniparc.to_csv(&mut self.uniparc, &self.format, &self.schema)?;
+        Ok(())
     }
 
-    for prop in properties.ncbi_taxonomy_id.iter() {
-        prop.to_csv(&mut headers.ncbi_taxonomy_id);
+    fn on_xref(&mut self, xref: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        xref.to_csv(&mut self.xref, &self.format, &self.schema)?;
+        Ok(())
     }
 
-    for prop in properties.pdb_chain.iter() {
-        prop.to_csv(&mut headers.pdb_chain);
+    fn on_property(&mut self, property: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        let output = property_output(
+            &mut self.properties,
+            &mut self.extra_properties,
+            &mut *self.create,
+            &property.table,
+        )?;
+        property.to_csv(output, &self.format, &self.schema)?;
+        Ok(())
     }
 
-    for prop in properties.protein_name.iter() {
-        prop.to_csv(&mut headers.protein_name);
+    fn on_domain(&mut self, domain: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        domain.to_csv(&mut self.domain, &self.format, &self.schema)?;
+        Ok(())
     }
 
-    for prop in properties.proteome_id.iter() {
-        prop.to_csv(&mut headers.proteome_id);
+    fn on_entry_ref(&mut self, entry: &UniparcEntryRef) -> Result<(), Box<dyn Error>> {
+        let format = &self.format;
+        let schema = &self.schema;
+        entry.uniparc.to_csv(&mut self.uniparc, format, schema)?;
+        for xref in &entry.xrefs {
+            xref.to_csv(&mut self.xref, format, schema)?;
+        }
+        let mut outputs = self.properties.named_mut();
+        let properties = entry.properties.named();
+        for (&mut (_, ref mut output), &(_, properties)) in
+            outputs.iter_mut().zip(properties.iter())
+        {
+            for property in properties {
+                property.to_csv(output, format, schema)?;
+            }
+        }
+        for property in &entry.extra_properties {
+            let output = property_output(
+                &mut self.properties,
+                &mut self.extra_properties,
+                &mut *self.create,
+                &property.table,
+            )?;
+            property.to_csv(output, format, schema)?;
+        }
+        for domain in &entry.domains {
+            domain.to_csv(&mut self.domain, format, schema)?;
+        }
+        Ok(())
     }
 
-    for prop in properties.uniprot_kb_accession.iter() {
-        prop.to_csv(&mut headers.uniprot_kb_accession);
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.uniparc.finalize()?;
+        self.domain.finalize()?;
+        self.xref.finalize()?;
+        for &mut (_, ref mut output) in self.properties.named_mut().iter_mut() {
+            output.finalize()?;
+        }
+        for output in self.extra_properties.values_mut() {
+            output.finalize()?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,121 @@
+extern crate arrow_array;
+extern crate arrow_ipc;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use arrow_array::{Array, StringArray};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use uniparc_xml_parser::{
+    initialize_outputs_arrow, open_inputs, run, ArrowIpcFormat, EntryBatcher, RunOptions,
+    SchemaOptions, UniparcReader,
+};
+
+fn open_sample() -> Box<dyn Read + Send> {
+    open_inputs(&["tests/data/uniparc_sample.xml"])
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn batches_have_fixed_size() {
+    let mut batcher = EntryBatcher::new(2);
+    let mut batches = Vec::new();
+    for entry in UniparcReader::new(BufReader::new(open_sample())) {
+        batches.extend(batcher.push(&entry.unwrap()));
+    }
+    let full_batches = batches.len();
+    batches.extend(batcher.finish());
+
+    // Only batches yielded by `push` are guaranteed to be full.
+    for (_, batch) in &batches[..full_batches] {
+        assert_eq!(batch.num_rows(), 2);
+    }
+    let num_rows = |table: &str| -> usize {
+        batches
+            .iter()
+            .filter(|&(name, _)| name == table)
+            .map(|(_, batch)| batch.num_rows())
+            .sum()
+    };
+    assert_eq!(num_rows("uniparc"), 3);
+    assert_eq!(num_rows("xref"), 6);
+    assert_eq!(num_rows("domain"), 5);
+    assert_eq!(num_rows("pdb_chain"), 1);
+
+    for (name, schema) in batcher.schemas() {
+        for (_, batch) in batches.iter().filter(|&(n, _)| n == name) {
+            assert_eq!(batch.schema(), schema);
+        }
+    }
+}
+
+#[test]
+fn writes_arrow_ipc_files() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs_arrow(
+        basedir.path(),
+        ArrowIpcFormat::File,
+        2,
+        &SchemaOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        run(open_sample(), handlers, RunOptions::default()).unwrap(),
+        3
+    );
+
+    let file = File::open(basedir.path().join("uniparc.arrow")).unwrap();
+    let reader = FileReader::try_new(file, None).unwrap();
+    let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(batches.len(), 2);
+    let uniparc_ids = batches[1]
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(uniparc_ids.value(0), "UPI0000000A03");
+    assert_eq!(uniparc_ids.null_count(), 0);
+}
+
+#[test]
+fn writes_arrow_ipc_streams() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs_arrow(
+        basedir.path(),
+        ArrowIpcFormat::Stream,
+        1024,
+        &SchemaOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        run(open_sample(), handlers, RunOptions::default()).unwrap(),
+        3
+    );
+
+    let file = File::open(basedir.path().join("xref.arrows")).unwrap();
+    let reader = StreamReader::try_new(file, None).unwrap();
+    let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].num_rows(), 6);
+}
+
+#[test]
+fn empty_batches_are_rejected() {
+    let basedir = tempfile::tempdir().unwrap();
+    let outputs = initialize_outputs_arrow(
+        basedir.path(),
+        ArrowIpcFormat::File,
+        0,
+        &SchemaOptions::default(),
+    );
+    assert!(outputs.is_err());
+}
+
+#[test]
+#[should_panic(expected = "batch size must be greater than 0")]
+fn empty_batches_are_rejected_by_the_batcher() {
+    EntryBatcher::new(0);
+}
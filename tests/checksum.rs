@@ -0,0 +1,136 @@
+extern crate uniparc_xml_parser;
+
+use std::fs;
+
+use uniparc_xml_parser::{
+    crc64_hex, md5_hex, parse_entry, refget, sha256_hex, ErrorPolicy, MemorySink, ParseErrorKind,
+    ParseOptions, RawEntry, RunOptions, SequenceCheck, SequenceDigests,
+};
+
+/// Human hemoglobin subunit alpha (P69905).
+const HBA: &str = "MVLSPADKTNVKAAWGKVGAHAGEYGAEALERMFLSFPTTKTYFPHFDLSHGSAQVKGHGKKVADALTNAVAHVDDMPNALSALSDLHAHKLRVDPVNFKLLSHCLLVTLAAHLPAEFTPAVHASLDKFLASVSTVLTSKYR";
+
+fn options(verify_sequences: SequenceCheck) -> ParseOptions {
+    ParseOptions {
+        verify_sequences,
+        ..ParseOptions::default()
+    }
+}
+
+fn entry(sequence: &str, length: usize, checksum: &str) -> RawEntry {
+    let data = format!(
+        r#"<entry dataset="uniparc">
+<accession>UPI0000000C01</accession>
+<sequence length="{}" checksum="{}">{}</sequence>
+</entry>"#,
+        length, checksum, sequence
+    );
+    RawEntry {
+        offset: 0,
+        data: data.into_bytes(),
+    }
+}
+
+#[test]
+fn computes_uniprot_crc64() {
+    assert_eq!(crc64_hex(b""), "0000000000000000");
+    assert_eq!(crc64_hex(HBA.as_bytes()), "15E13666573BBBAE");
+}
+
+#[test]
+fn computes_digests() {
+    assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(md5_hex(HBA.as_bytes()), "6077c452d1dc6151040b2b179e2294c7");
+    assert_eq!(
+        sha256_hex(HBA.as_bytes()),
+        "14725a10598943a7aa719eed7d24c7fee599192a6c63c75b051ee6f156341242"
+    );
+    assert_eq!(
+        refget(HBA.as_bytes()),
+        "SQ.YxUCon6qCiepFh3SHgyHCAvyq2E-KSdK"
+    );
+}
+
+#[test]
+fn digests_are_only_computed_when_enabled() {
+    let raw_entry = entry("MKV", 3, &crc64_hex(b"MKV"));
+    let entry = parse_entry(&raw_entry, &ParseOptions::default()).unwrap();
+    assert_eq!(entry.uniparc.sequence_md5, None);
+    assert_eq!(entry.uniparc.sequence_sha256, None);
+    assert_eq!(entry.uniparc.sequence_refget, None);
+
+    let mut digests = SequenceDigests::default();
+    assert!(digests.enable("md5"));
+    assert!(digests.enable("refget"));
+    assert!(!digests.enable("sha1"));
+    let options = ParseOptions {
+        digests,
+        ..ParseOptions::default()
+    };
+    let entry = parse_entry(&raw_entry, &options).unwrap();
+    assert_eq!(entry.uniparc.sequence_md5, Some(md5_hex(b"MKV")));
+    assert_eq!(entry.uniparc.sequence_sha256, None);
+    assert_eq!(entry.uniparc.sequence_refget, Some(refget(b"MKV")));
+}
+
+#[test]
+fn sample_sequences_are_valid() {
+    let input = fs::read("tests/data/uniparc_sample.xml").unwrap();
+    let options = RunOptions {
+        parse: options(SequenceCheck::Reject),
+        ..RunOptions::default()
+    };
+    let count = uniparc_xml_parser::run(&input[..], MemorySink::new(), options).unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn rejects_checksum_mismatch() {
+    let raw_entry = entry("MKV", 3, "0000000000000001");
+    let error = parse_entry(&raw_entry, &options(SequenceCheck::Reject)).unwrap_err();
+    assert_eq!(error.uniparc_id, "UPI0000000C01");
+    match error.kind {
+        ParseErrorKind::SequenceChecksum { expected, actual } => {
+            assert_eq!(expected, "0000000000000001");
+            assert_eq!(actual, crc64_hex(b"MKV"));
+        }
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn rejects_length_mismatch() {
+    let raw_entry = entry("MKV", 4, &crc64_hex(b"MKV"));
+    let error = parse_entry(&raw_entry, &options(SequenceCheck::Reject)).unwrap_err();
+    match error.kind {
+        ParseErrorKind::SequenceLength { expected, actual } => {
+            assert_eq!((expected, actual), (4, 3));
+        }
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn reports_or_ignores_mismatch() {
+    let raw_entry = entry("MKV", 4, "0000000000000001");
+    for &check in &[SequenceCheck::Off, SequenceCheck::Report] {
+        let entry = parse_entry(&raw_entry, &options(check)).unwrap();
+        assert_eq!(entry.uniparc.sequence_length, 4);
+    }
+}
+
+#[test]
+fn rejected_sequences_follow_error_policy() {
+    let sample = fs::read_to_string("tests/data/uniparc_sample.xml").unwrap();
+    let input = sample.replacen("15E13666573BBBAE", "15E13666573BBBAF", 1);
+    let mut sink = MemorySink::new();
+    let options = RunOptions {
+        on_error: ErrorPolicy::Skip,
+        parse: options(SequenceCheck::Reject),
+        ..RunOptions::default()
+    };
+    let count = uniparc_xml_parser::run(input.as_bytes(), &mut sink, options).unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(sink.uniparc.len(), 2);
+    assert_eq!(sink.uniparc[0].uniparc_id, "UPI0000000A02");
+}
@@ -0,0 +1,31 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use std::fs;
+
+use uniparc_xml_parser::{run, MemorySink, ParseOptions, RunOptions};
+
+/// Path of the sample UniParc XML file.
+pub const SAMPLE: &str = "tests/data/uniparc_sample.xml";
+
+/// Contents of the sample UniParc XML file.
+pub fn sample() -> Vec<u8> {
+    fs::read(SAMPLE).unwrap()
+}
+
+/// Parse `input` into a `MemorySink`, returning the number of entries and the sink.
+pub fn parse(input: &[u8], options: RunOptions) -> (usize, MemorySink) {
+    let mut sink = MemorySink::new();
+    let count = run(input, &mut sink, options).unwrap();
+    (count, sink)
+}
+
+/// Parse the sample file on `threads` threads.
+pub fn parse_sample(options: ParseOptions, threads: usize) -> MemorySink {
+    let options = RunOptions {
+        parse: options,
+        threads,
+        ..RunOptions::default()
+    };
+    parse(&sample(), options).1
+}
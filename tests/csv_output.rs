@@ -0,0 +1,141 @@
+extern crate csv;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+use uniparc_xml_parser::writer::Writable;
+use uniparc_xml_parser::{
+    initialize_outputs, CsvFormat, Escape, QuoteStyle, SchemaOptions, UniparcProperty,
+};
+
+const VALUES: &[&str] = &[
+    "plain",
+    "",
+    "He said \"hi\"",
+    "tab\tseparated",
+    "comma, separated",
+    "multi\nline\r\nvalue",
+    "back\\slash",
+    "ünïcödé ☃",
+];
+
+fn properties() -> Vec<UniparcProperty> {
+    VALUES
+        .iter()
+        .enumerate()
+        .map(|(i, value)| UniparcProperty {
+            uniparc_id: String::from("UPI0000000A01"),
+            xref_id: i as u64 + 1,
+            property: String::from("protein_name"),
+            value: value.to_string(),
+            rank: None,
+            lineage: None,
+            table: String::from("protein_name"),
+        })
+        .collect()
+}
+
+fn write(format: &CsvFormat) -> Vec<u8> {
+    let mut output = Vec::new();
+    for property in properties() {
+        property
+            .to_csv(&mut output, format, &SchemaOptions::default())
+            .unwrap();
+    }
+    output
+}
+
+fn round_trip(format: &CsvFormat) {
+    let output = write(format);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(format.delimiter)
+        .quote(format.quote)
+        .from_reader(&output[..]);
+    let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), VALUES.len());
+    for (record, property) in records.iter().zip(properties()) {
+        assert_eq!(&record[0], property.uniparc_id);
+        assert_eq!(&record[1], property.xref_id.to_string());
+        assert_eq!(&record[2], property.property);
+        assert_eq!(&record[3], property.value);
+    }
+}
+
+#[test]
+fn round_trips_default_format() {
+    round_trip(&CsvFormat::default());
+}
+
+#[test]
+fn round_trips_rfc4180() {
+    round_trip(&CsvFormat::rfc4180());
+}
+
+#[test]
+fn round_trips_custom_format() {
+    round_trip(&CsvFormat {
+        delimiter: b';',
+        quote: b'\'',
+        quote_style: QuoteStyle::Always,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn writes_rfc4180_quoting() {
+    let output = String::from_utf8(write(&CsvFormat::rfc4180())).unwrap();
+    let lines = output.split("\r\n").collect::<Vec<_>>();
+    assert_eq!(lines[0], "UPI0000000A01,1,protein_name,plain");
+    assert_eq!(lines[1], "UPI0000000A01,2,protein_name,\"\"");
+    assert_eq!(
+        lines[2],
+        "UPI0000000A01,3,protein_name,\"He said \"\"hi\"\"\""
+    );
+    assert_eq!(
+        lines[4],
+        "UPI0000000A01,5,protein_name,\"comma, separated\""
+    );
+}
+
+#[test]
+fn writes_backslash_escapes() {
+    let format = CsvFormat {
+        quote_style: QuoteStyle::Never,
+        escape: Escape::Backslash,
+        null: String::from("\\N"),
+        ..Default::default()
+    };
+    let output = String::from_utf8(write(&format)).unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), VALUES.len());
+    assert_eq!(lines[2], "UPI0000000A01\t3\tprotein_name\tHe said \"hi\"");
+    assert_eq!(lines[3], "UPI0000000A01\t4\tprotein_name\ttab\\tseparated");
+    assert_eq!(
+        lines[5],
+        "UPI0000000A01\t6\tprotein_name\tmulti\\nline\\r\\nvalue"
+    );
+    assert_eq!(lines[6], "UPI0000000A01\t7\tprotein_name\tback\\\\slash");
+}
+
+#[test]
+fn ambiguous_formats_are_rejected() {
+    let format = CsvFormat {
+        quote_style: QuoteStyle::Never,
+        ..Default::default()
+    };
+    assert!(format.validate().is_err());
+    let basedir = tempfile::tempdir().unwrap();
+    assert!(initialize_outputs(
+        basedir.path().to_path_buf(),
+        format,
+        SchemaOptions::default()
+    )
+    .is_err());
+
+    let format = CsvFormat {
+        quote: b'\t',
+        ..Default::default()
+    };
+    assert!(format.validate().is_err());
+    assert!(CsvFormat::rfc4180().validate().is_ok());
+}
@@ -0,0 +1,58 @@
+extern crate uniparc_xml_parser;
+
+use std::fs::File;
+use std::io::BufReader;
+
+use uniparc_xml_parser::{DbCategory, DbType, UniparcReader};
+
+#[test]
+fn known_names_round_trip() {
+    for db_type in DbType::KNOWN {
+        assert_eq!(&DbType::from_name(db_type.name()), db_type);
+        assert_ne!(db_type.category(), DbCategory::Other);
+    }
+}
+
+#[test]
+fn unknown_names_are_kept() {
+    let db_type = DbType::from_name("NewDB");
+    assert_eq!(db_type, DbType::Other(String::from("NewDB")));
+    assert_eq!(db_type.name(), "NewDB");
+    assert_eq!(db_type.category(), DbCategory::Other);
+    assert_eq!(db_type.url("X1"), None);
+}
+
+#[test]
+fn builds_urls() {
+    assert_eq!(
+        DbType::Pdb.url("1A00"),
+        Some(String::from("https://www.rcsb.org/structure/1A00"))
+    );
+    assert_eq!(
+        DbType::SwissProt.url("P69905"),
+        Some(String::from("https://www.uniprot.org/uniprotkb/P69905"))
+    );
+}
+
+#[test]
+fn reads_db_types() {
+    let file = File::open("tests/data/uniparc_sample.xml").unwrap();
+    let entry = UniparcReader::new(BufReader::new(file))
+        .next()
+        .unwrap()
+        .unwrap();
+    let db_types = entry
+        .xrefs
+        .iter()
+        .map(|x| (x.db_type.clone(), x.db_type.category()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        db_types,
+        vec![
+            (DbType::SwissProt, DbCategory::UniProt),
+            (DbType::Pdb, DbCategory::Structure),
+            (DbType::RefSeq, DbCategory::Sequence),
+            (DbType::Ensembl, DbCategory::Genome),
+        ]
+    );
+}
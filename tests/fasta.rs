@@ -0,0 +1,179 @@
+extern crate uniparc_xml_parser;
+
+use std::fs;
+
+use uniparc_xml_parser::{
+    run, DomainFastaOptions, DomainFastaSink, FastaHeader, FastaOptions, FastaSink, ParseOptions,
+    RunOptions, Sink, UniparcReader,
+};
+
+const TEMPLATE: &str = "{uniparc_id} {protein_name} OX={ncbi_taxonomy_id} len={sequence_length}";
+
+fn options(template: &str, line_width: usize) -> FastaOptions {
+    FastaOptions {
+        header: FastaHeader::parse(template, &ParseOptions::default()).unwrap(),
+        line_width,
+    }
+}
+
+fn write_fasta(options: FastaOptions) -> String {
+    let input = fs::read("tests/data/uniparc_sample.xml").unwrap();
+    let mut sink = FastaSink::new(Vec::new(), options);
+    run(&input[..], &mut sink, RunOptions::default()).unwrap();
+    String::from_utf8(sink.into_inner()).unwrap()
+}
+
+#[test]
+fn writes_headers_from_template() {
+    let fasta = write_fasta(options(TEMPLATE, 0));
+    let headers = fasta
+        .lines()
+        .filter(|line| line.starts_with('>'))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headers,
+        vec![
+            ">UPI0000000A01 Hemoglobin subunit alpha;hemoglobin subunit alpha OX=9606 len=142",
+            ">UPI0000000A02 Uncharacterized protein OX=10090 len=250",
+            ">UPI0000000A03 Putative transcriptional regulator OX=562 len=33",
+        ]
+    );
+    assert_eq!(fasta.lines().count(), 6);
+}
+
+#[test]
+fn wraps_sequences() {
+    let fasta = write_fasta(options("{uniparc_id}", 60));
+    let record = fasta.split('>').nth(2).unwrap();
+    let lengths = record.lines().map(str::len).collect::<Vec<_>>();
+    assert_eq!(lengths, vec![13, 60, 60, 60, 60, 10]);
+}
+
+#[test]
+fn entries_and_rows_give_the_same_records() {
+    let file = fs::File::open("tests/data/uniparc_sample.xml").unwrap();
+    let mut sink = FastaSink::new(Vec::new(), options(TEMPLATE, 60));
+    for entry in UniparcReader::new(std::io::BufReader::new(file)) {
+        sink.on_entry(&entry.unwrap()).unwrap();
+    }
+    sink.finish().unwrap();
+    let fasta = String::from_utf8(sink.into_inner()).unwrap();
+    assert_eq!(fasta, write_fasta(options(TEMPLATE, 60)));
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    let options = ParseOptions::default();
+    assert!(FastaHeader::parse("{uniparc_id", &options).is_err());
+    assert!(FastaHeader::parse("{}", &options).is_err());
+    assert!(FastaHeader::parse("{protein name}", &options).is_err());
+    assert_eq!(
+        FastaHeader::parse("{uniparc_id}", &options).unwrap(),
+        FastaHeader::default()
+    );
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let mut options = ParseOptions::default();
+    assert!(FastaHeader::parse("{uniparc_id} {protein_nmae}", &options).is_err());
+    assert!(FastaHeader::parse("{sequence}", &options).is_err());
+    assert!(FastaHeader::parse("{pdb_chain} {isoform_name}", &options).is_err());
+    assert!(FastaHeader::parse("{sequence_md5}", &options).is_err());
+
+    options.digests.enable("md5");
+    options
+        .properties
+        .insert("isoform_name", "isoform_name")
+        .unwrap();
+    assert!(FastaHeader::parse("{sequence_md5}", &options).is_ok());
+    assert!(FastaHeader::parse("{pdb_chain} {isoform_name}", &options).is_ok());
+    assert!(FastaHeader::parse("{sequence_refget}", &options).is_err());
+}
+
+/// Two entries with the same sequence and Pfam domain, and a domain outside of the sequence.
+const DUPLICATE_DOMAINS: &[u8] = br#"<uniparc>
+<entry dataset="uniparc">
+<accession>UPI0000000D01</accession>
+<signatureSequenceMatch database="Pfam" id="PF00001">
+<lcn start="2" end="4"/>
+</signatureSequenceMatch>
+<signatureSequenceMatch database="Gene3D" id="G3DSA:1.10.1.10">
+<lcn start="1" end="5"/>
+</signatureSequenceMatch>
+<sequence length="5" checksum="0000000000000000">MKVLA</sequence>
+</entry>
+<entry dataset="uniparc">
+<accession>UPI0000000D02</accession>
+<signatureSequenceMatch database="Pfam" id="PF00001">
+<lcn start="2" end="4"/>
+</signatureSequenceMatch>
+<signatureSequenceMatch database="Pfam" id="PF00002">
+<lcn start="4" end="9"/>
+</signatureSequenceMatch>
+<sequence length="5" checksum="0000000000000000">MKVLA</sequence>
+</entry>
+</uniparc>"#;
+
+fn write_domain_fasta(input: &[u8], options: DomainFastaOptions) -> String {
+    let mut sink = DomainFastaSink::new(Vec::new(), options);
+    run(input, &mut sink, RunOptions::default()).unwrap();
+    String::from_utf8(sink.into_inner()).unwrap()
+}
+
+#[test]
+fn writes_domain_sequences() {
+    let options = DomainFastaOptions {
+        databases: vec![String::from("Gene3D")],
+        ..DomainFastaOptions::default()
+    };
+    let fasta = write_domain_fasta(DUPLICATE_DOMAINS, options);
+    assert_eq!(fasta, ">UPI0000000D01/1-5 Gene3D:G3DSA:1.10.1.10\nMKVLA\n");
+
+    // Database names are matched ignoring case.
+    let input = fs::read("tests/data/uniparc_sample.xml").unwrap();
+    let options = DomainFastaOptions {
+        databases: vec![String::from("pfam")],
+        line_width: 0,
+        ..DomainFastaOptions::default()
+    };
+    let fasta = write_domain_fasta(&input, options);
+    assert_eq!(
+        fasta.lines().collect::<Vec<_>>(),
+        vec![
+            ">UPI0000000A01/27-138 Pfam:PF00042 IPR000971",
+            "AEALERMFLSFPTTKTYFPHFDLSHGSAQVKGHGKKVADALTNAVAHVDDMPNALSALSDLHAHKLRVDPVNFKLLSHCLLVTLAAHLPAEFTPAVHASLDKFLASVSTVLT",
+            ">UPI0000000A03/5-30 Pfam:PF01047",
+            "YIAKQRQISFVKSHFSRQLEERLGLI",
+        ]
+    );
+}
+
+#[test]
+fn deduplicates_domain_sequences() {
+    let options = DomainFastaOptions {
+        databases: vec![String::from("Pfam")],
+        ..DomainFastaOptions::default()
+    };
+    let fasta = write_domain_fasta(DUPLICATE_DOMAINS, options.clone());
+    assert_eq!(
+        fasta,
+        ">UPI0000000D01/2-4 Pfam:PF00001\nKVL\n>UPI0000000D02/2-4 Pfam:PF00001\nKVL\n"
+    );
+
+    let options = DomainFastaOptions {
+        deduplicate: true,
+        ..options
+    };
+    let fasta = write_domain_fasta(DUPLICATE_DOMAINS, options);
+    assert_eq!(fasta, ">UPI0000000D01/2-4 Pfam:PF00001\nKVL\n");
+}
+
+#[test]
+fn counts_domains_outside_of_their_sequence() {
+    let mut sink = DomainFastaSink::new(Vec::new(), DomainFastaOptions::default());
+    run(DUPLICATE_DOMAINS, &mut sink, RunOptions::default()).unwrap();
+    assert_eq!(sink.skipped_domains(), 1);
+    let fasta = String::from_utf8(sink.into_inner()).unwrap();
+    assert!(!fasta.contains("PF00002"));
+}
@@ -0,0 +1,214 @@
+extern crate uniparc_xml_parser;
+
+mod common;
+
+use uniparc_xml_parser::{
+    read_taxa, DbFilter, DbType, DomainFilter, FilterScope, MemorySink, ParseOptions, TaxonFilter,
+    UniparcReader,
+};
+
+fn taxa(taxa: &[u32], scope: FilterScope) -> ParseOptions {
+    ParseOptions {
+        taxa: Some(TaxonFilter::new(taxa.iter().cloned(), scope)),
+        ..ParseOptions::default()
+    }
+}
+
+fn uniparc_ids(sink: &MemorySink) -> Vec<&str> {
+    sink.uniparc.iter().map(|u| &u.uniparc_id[..]).collect()
+}
+
+#[test]
+fn keeps_entries_of_taxa() {
+    let sink = common::parse_sample(taxa(&[9606, 562], FilterScope::Entries), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01", "UPI0000000A03"]);
+    // Cross-references without a taxonomy ID are kept with their entry.
+    assert_eq!(sink.xrefs.len(), 5);
+    assert!(sink.xrefs.iter().any(|x| x.db_id == "ENSP00000251595"));
+    assert!(sink.domains.iter().all(|d| d.uniparc_id != "UPI0000000A02"));
+}
+
+#[test]
+fn keeps_xrefs_of_taxa() {
+    let sink = common::parse_sample(taxa(&[9606], FilterScope::XRefs), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+    let xrefs = sink
+        .xrefs
+        .iter()
+        .map(|x| (x.xref_id, &x.db_id[..]))
+        .collect::<Vec<_>>();
+    assert_eq!(xrefs, vec![(1, "P69905"), (2, "1A00"), (3, "NP_000549")]);
+    assert!(sink
+        .properties
+        .iter()
+        .all(|p| xrefs.iter().any(|&(xref_id, _)| xref_id == p.xref_id)));
+}
+
+#[test]
+fn filters_are_applied_by_all_readers() {
+    for &scope in &[FilterScope::Entries, FilterScope::XRefs] {
+        let sequential = common::parse_sample(taxa(&[10090, 562], scope), 1);
+        let parallel = common::parse_sample(taxa(&[10090, 562], scope), 3);
+        assert_eq!(parallel.uniparc, sequential.uniparc);
+        assert_eq!(parallel.xrefs, sequential.xrefs);
+        assert_eq!(parallel.properties, sequential.properties);
+
+        let input = common::sample();
+        let entries = UniparcReader::with_options(&input[..], taxa(&[10090, 562], scope))
+            .map(|entry| entry.unwrap().uniparc)
+            .collect::<Vec<_>>();
+        assert_eq!(entries, sequential.uniparc);
+    }
+}
+
+#[test]
+fn reads_taxa() {
+    let input = "# Human and mouse\n9606\n10090 562\n\n";
+    assert_eq!(read_taxa(input.as_bytes()).unwrap(), vec![9606, 10090, 562]);
+    assert!(read_taxa("9606\nhuman\n".as_bytes()).is_err());
+}
+
+fn databases(databases: DbFilter) -> ParseOptions {
+    ParseOptions {
+        databases,
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn requires_and_excludes_databases() {
+    let filter = DbFilter {
+        require: vec![DbType::Pdb, DbType::TrEmbl],
+        ..DbFilter::default()
+    };
+    let sink = common::parse_sample(databases(filter), 1);
+    assert_eq!(
+        uniparc_ids(&sink),
+        vec!["UPI0000000A01", "UPI0000000A02", "UPI0000000A03"]
+    );
+
+    let filter = DbFilter {
+        require: vec![DbType::SwissProt],
+        ..DbFilter::default()
+    };
+    let sink = common::parse_sample(databases(filter), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+    assert_eq!(sink.xrefs.len(), 4);
+
+    let filter = DbFilter {
+        exclude: vec![DbType::Pdb],
+        ..DbFilter::default()
+    };
+    let sink = common::parse_sample(databases(filter), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A02", "UPI0000000A03"]);
+
+    // Inactive cross-references are only taken into account if they are kept.
+    let filter = DbFilter {
+        exclude: vec![DbType::Embl],
+        ..DbFilter::default()
+    };
+    assert_eq!(
+        common::parse_sample(databases(filter.clone()), 1)
+            .uniparc
+            .len(),
+        3
+    );
+    let options = ParseOptions {
+        include_inactive: true,
+        ..databases(filter)
+    };
+    assert_eq!(common::parse_sample(options, 1).uniparc.len(), 2);
+}
+
+#[test]
+fn restricts_xref_databases() {
+    let filter = DbFilter {
+        xrefs: vec![DbType::Pdb],
+        ..DbFilter::default()
+    };
+    let sink = common::parse_sample(databases(filter), 1);
+    assert_eq!(sink.uniparc.len(), 3);
+    assert_eq!(
+        sink.domains,
+        common::parse_sample(ParseOptions::default(), 1).domains
+    );
+    let xrefs = sink
+        .xrefs
+        .iter()
+        .map(|x| (&x.uniparc_id[..], x.xref_id))
+        .collect::<Vec<_>>();
+    assert_eq!(xrefs, vec![("UPI0000000A01", 2)]);
+    assert!(sink.properties.iter().all(|p| p.xref_id == 2));
+    assert!(sink.properties.iter().any(|p| p.property == "pdb_chain"));
+}
+
+#[test]
+fn entries_are_selected_before_xrefs_are_removed() {
+    // The Ensembl cross-reference of the human entry has no taxonomy ID itself.
+    let options = ParseOptions {
+        databases: DbFilter {
+            xrefs: vec![DbType::Ensembl],
+            ..DbFilter::default()
+        },
+        ..taxa(&[9606], FilterScope::Entries)
+    };
+    for &threads in &[1, 2] {
+        let sink = common::parse_sample(options.clone(), threads);
+        assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+        let db_types = sink.xrefs.iter().map(|x| &x.db_type).collect::<Vec<_>>();
+        assert_eq!(db_types, vec![&DbType::Ensembl]);
+        assert!(sink.properties.is_empty());
+    }
+
+    // With both filters restricting cross-references, only those matching both are kept.
+    let options = ParseOptions {
+        databases: DbFilter {
+            xrefs: vec![DbType::Pdb, DbType::Ensembl],
+            ..DbFilter::default()
+        },
+        ..taxa(&[9606], FilterScope::XRefs)
+    };
+    let sink = common::parse_sample(options, 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+    let db_types = sink.xrefs.iter().map(|x| &x.db_type).collect::<Vec<_>>();
+    assert_eq!(db_types, vec![&DbType::Pdb]);
+    assert!(sink
+        .properties
+        .iter()
+        .all(|p| p.xref_id == sink.xrefs[0].xref_id));
+}
+
+#[test]
+fn keeps_entries_with_domains() {
+    let filter = |databases: &[&str], interpro_ids: &[&str]| ParseOptions {
+        domains: DomainFilter {
+            databases: databases.iter().map(|d| d.to_string()).collect(),
+            interpro_ids: interpro_ids.iter().map(|id| id.to_string()).collect(),
+        },
+        ..ParseOptions::default()
+    };
+    let sink = common::parse_sample(filter(&["Pfam"], &[]), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01", "UPI0000000A03"]);
+    assert_eq!(sink.domains.len(), 5);
+
+    let sink = common::parse_sample(filter(&["Gene3D", "SMART"], &[]), 2);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+
+    let sink = common::parse_sample(filter(&[], &["IPR012292"]), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+
+    // Entries with a domain matching either the database or the InterPro ID are kept.
+    let sink = common::parse_sample(filter(&["Pfam"], &["IPR012292"]), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01", "UPI0000000A03"]);
+    let sink = common::parse_sample(filter(&["SMART"], &["IPR012292"]), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+
+    // Database names and InterPro IDs are compared ignoring case.
+    let sink = common::parse_sample(filter(&["pfam"], &[]), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01", "UPI0000000A03"]);
+    let sink = common::parse_sample(filter(&["SMART"], &["ipr012292"]), 1);
+    assert_eq!(uniparc_ids(&sink), vec!["UPI0000000A01"]);
+    let sink = common::parse_sample(filter(&["SMART"], &["IPR999999"]), 1);
+    assert!(sink.uniparc.is_empty());
+    assert!(sink.xrefs.is_empty());
+}
@@ -0,0 +1,62 @@
+extern crate bzip2;
+extern crate flate2;
+extern crate uniparc_xml_parser;
+extern crate xz2;
+extern crate zstd;
+
+use std::fs;
+use std::io::{Cursor, Read, Write};
+
+use uniparc_xml_parser::decompress;
+
+fn sample() -> Vec<u8> {
+    fs::read("tests/data/uniparc_sample.xml").unwrap()
+}
+
+fn decompress_all(data: Vec<u8>) -> Vec<u8> {
+    let mut output = Vec::new();
+    decompress(Cursor::new(data))
+        .unwrap()
+        .read_to_end(&mut output)
+        .unwrap();
+    output
+}
+
+#[test]
+fn passes_through_plain_input() {
+    assert_eq!(decompress_all(sample()), sample());
+    assert_eq!(decompress_all(Vec::new()), Vec::<u8>::new());
+}
+
+#[test]
+fn decompresses_multi_member_gzip() {
+    let sample = sample();
+    let (first, second) = sample.split_at(1000);
+    let mut data = Vec::new();
+    for part in &[first, second] {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(part).unwrap();
+        data.extend(encoder.finish().unwrap());
+    }
+    assert_eq!(decompress_all(data), sample);
+}
+
+#[test]
+fn decompresses_zstd() {
+    let data = zstd::stream::encode_all(Cursor::new(sample()), 0).unwrap();
+    assert_eq!(decompress_all(data), sample());
+}
+
+#[test]
+fn decompresses_xz() {
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&sample()).unwrap();
+    assert_eq!(decompress_all(encoder.finish().unwrap()), sample());
+}
+
+#[test]
+fn decompresses_bzip2() {
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+    encoder.write_all(&sample()).unwrap();
+    assert_eq!(decompress_all(encoder.finish().unwrap()), sample());
+}
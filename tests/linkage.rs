@@ -0,0 +1,180 @@
+extern crate csv;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use uniparc_xml_parser::{
+    initialize_outputs, run, CsvFormat, MemorySink, ParseErrorKind, ParseOptions, Properties,
+    RunOptions, SchemaOptions, UniparcReader,
+};
+
+/// Entry with an inactive cross-reference that has properties, followed by an active
+/// cross-reference without any (an empty element), and an active one with properties.
+const TRICKY_ENTRY: &str = r#"<uniparc><entry dataset="uniparc">
+<accession>UPI0000000B01</accession>
+<dbReference type="EMBL" id="CAA00001" version_i="1" active="N" version="1" created="1991-05-19" last="2010-04-02">
+<property type="NCBI_GI" value="1"/>
+<property type="protein_name" value="inactive"/>
+</dbReference>
+<dbReference type="Ensembl" id="ENSP00000000001" version_i="1" active="Y" version="1" created="2005-03-15" last="2020-08-12"/>
+<dbReference type="RefSeq" id="NP_000001" version_i="1" active="Y" version="1" created="2003-03-04" last="2020-08-12">
+<property type="NCBI_GI" value="2"/>
+<property type="protein_name" value="active"/>
+</dbReference>
+<dbReference type="PDB" id="1B00" version_i="1" active="N" created="1998-02-27" last="2020-08-12"/>
+<dbReference type="PDB" id="1C00" version_i="1" active="Y" created="1998-02-27" last="2020-08-12">
+<property type="chain" value="A"/>
+</dbReference>
+<sequence length="3" checksum="0000000000000000">MKV</sequence>
+</entry></uniparc>"#;
+
+fn parse(input: &[u8], threads: usize, include_inactive: bool) -> MemorySink {
+    let options = RunOptions {
+        parse: ParseOptions {
+            include_inactive,
+            ..ParseOptions::default()
+        },
+        threads,
+        ..RunOptions::default()
+    };
+    common::parse(input, options).1
+}
+
+/// Map each property value to the `db_id` of the single xref row it joins to.
+fn join(sink: &MemorySink) -> Vec<(String, String)> {
+    let mut xrefs = HashMap::new();
+    for xref in &sink.xrefs {
+        let key = (xref.uniparc_id.as_str(), xref.xref_id);
+        assert!(
+            xrefs.insert(key, xref.db_id.as_str()).is_none(),
+            "Duplicate xref {:?}",
+            key
+        );
+    }
+    sink.properties
+        .iter()
+        .map(|property| {
+            let key = (property.uniparc_id.as_str(), property.xref_id);
+            let db_id = xrefs
+                .get(&key)
+                .unwrap_or_else(|| panic!("No xref for property {:?}", property));
+            (property.value.clone(), db_id.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn sample_properties_join_to_one_xref() {
+    let input = common::sample();
+    for &include_inactive in &[false, true] {
+        let sequential = join(&parse(&input, 1, include_inactive));
+        assert!(!sequential.is_empty());
+        for &threads in &[2, 4] {
+            assert_eq!(join(&parse(&input, threads, include_inactive)), sequential);
+        }
+    }
+}
+
+#[test]
+fn sample_properties_join_to_enclosing_xref() {
+    let input = common::sample();
+    let joined = join(&parse(&input, 1, true));
+    assert!(joined.contains(&("31719".to_string(), "CAA23748".to_string())));
+    assert!(joined.contains(&("1A00A".to_string(), "1A00".to_string())));
+    assert!(joined.contains(&("4504347".to_string(), "NP_000549".to_string())));
+    assert!(joined.contains(&("Q9BX83".to_string(), "Q9BX83".to_string())));
+}
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|&(value, db_id)| (value.to_string(), db_id.to_string()))
+        .collect()
+}
+
+#[test]
+fn properties_of_skipped_xrefs_are_dropped() {
+    let mut joined = join(&parse(TRICKY_ENTRY.as_bytes(), 1, false));
+    joined.sort();
+    let expected = pairs(&[
+        ("1C00A", "1C00"),
+        ("2", "NP_000001"),
+        ("active", "NP_000001"),
+    ]);
+    assert_eq!(joined, expected);
+}
+
+#[test]
+fn properties_of_inactive_xrefs_are_kept() {
+    let sink = parse(TRICKY_ENTRY.as_bytes(), 1, true);
+    assert_eq!(sink.xrefs.len(), 5);
+    let mut joined = join(&sink);
+    joined.sort();
+    let expected = pairs(&[
+        ("1", "CAA00001"),
+        ("1C00A", "1C00"),
+        ("2", "NP_000001"),
+        ("active", "NP_000001"),
+        ("inactive", "CAA00001"),
+    ]);
+    assert_eq!(joined, expected);
+}
+
+#[test]
+fn property_outside_xref_is_an_error() {
+    let data = br#"<uniparc><entry dataset="uniparc">
+<accession>UPI0000000B01</accession>
+<dbReference type="Ensembl" id="ENSP00000000001" version_i="1" active="Y" version="1" created="2005-03-15" last="2020-08-12"/>
+<property type="NCBI_GI" value="1"/>
+<sequence length="3" checksum="0000000000000000">MKV</sequence>
+</entry></uniparc>"#;
+    let error = UniparcReader::new(&data[..]).next().unwrap().unwrap_err();
+    match error.kind {
+        ParseErrorKind::UnexpectedElement { element } => assert_eq!(element, "property"),
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
+
+fn read_tsv(path: &Path) -> Vec<csv::StringRecord> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(b'\t')
+        .from_path(path)
+        .unwrap()
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn tsv_property_rows_join_to_one_xref_row() {
+    let basedir = tempfile::tempdir().unwrap();
+    let input = common::sample();
+    let sink = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    run(&input[..], sink, RunOptions::default()).unwrap();
+
+    let mut xrefs = HashMap::new();
+    for record in read_tsv(&basedir.path().join("xref.tsv")) {
+        let key = (record[0].to_string(), record[1].to_string());
+        *xrefs.entry(key).or_insert(0) += 1;
+    }
+
+    let mut num_properties = 0;
+    for &(name, _) in Properties::from_fn(|_| ()).named().iter() {
+        for record in read_tsv(&basedir.path().join(format!("{}.tsv", name))) {
+            let key = (record[0].to_string(), record[1].to_string());
+            assert_eq!(xrefs.get(&key), Some(&1), "{} row {:?}", name, record);
+            num_properties += 1;
+        }
+    }
+    assert!(num_properties > 0);
+}
@@ -0,0 +1,162 @@
+extern crate arrow_array;
+extern crate arrow_schema;
+extern crate parquet;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+use std::fs::File;
+use std::path::Path;
+
+use arrow_array::{Array, BooleanArray, Date32Array, Int32Array, Int64Array, StringArray};
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use uniparc_xml_parser::{
+    initialize_outputs_parquet, open_inputs, run_inputs, ParquetCompression, ParquetOptions,
+    ParseOptions, PropertyMapping, RunOptions, SchemaOptions,
+};
+
+#[test]
+fn writes_parquet_tables() {
+    let basedir = tempfile::tempdir().unwrap();
+    let options = ParquetOptions {
+        row_group_size: 2,
+        compression: ParquetCompression::Snappy,
+    };
+    let handlers =
+        initialize_outputs_parquet(basedir.path(), &options, &SchemaOptions::default()).unwrap();
+    let input = open_inputs(&["tests/data/uniparc_sample.xml"]).unwrap();
+    assert_eq!(
+        run_inputs(input, handlers, RunOptions::default()).unwrap(),
+        3
+    );
+
+    let file = File::open(basedir.path().join("xref.parquet")).unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    assert_eq!(builder.metadata().num_row_groups(), 3);
+    let schema = builder.schema().clone();
+    let column_names = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        column_names,
+        vec![
+            "uniparc_id",
+            "xref_id",
+            "db_type",
+            "db_id",
+            "version_i",
+            "active",
+            "version",
+            "created",
+            "last"
+        ]
+    );
+
+    let batches = builder
+        .build()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let num_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    assert_eq!(num_rows, 6);
+
+    let first = &batches[0];
+    let db_ids = first
+        .column(3)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(db_ids.value(0), "P69905");
+    let xref_ids = first
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(xref_ids.value(1), 2);
+    assert_eq!(xref_ids.null_count(), 0);
+
+    let data_types = schema
+        .fields()
+        .iter()
+        .skip(4)
+        .map(|f| (f.data_type().clone(), f.is_nullable()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        data_types,
+        vec![
+            (DataType::Int32, false),
+            (DataType::Boolean, false),
+            (DataType::Int32, true),
+            (DataType::Date32, false),
+            (DataType::Date32, false),
+        ]
+    );
+    let active = first
+        .column(5)
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .unwrap();
+    assert!(active.value(0));
+    // The PDB cross-reference does not have a version.
+    let versions = first
+        .column(6)
+        .as_any()
+        .downcast_ref::<Int32Array>()
+        .unwrap();
+    assert_eq!((versions.value(0), versions.is_null(1)), (2, true));
+    let created = first
+        .column(7)
+        .as_any()
+        .downcast_ref::<Date32Array>()
+        .unwrap();
+    assert_eq!(created.value_as_date(0).unwrap().to_string(), "2005-03-15");
+}
+
+#[test]
+fn empty_row_groups_are_rejected() {
+    let basedir = tempfile::tempdir().unwrap();
+    let options = ParquetOptions {
+        row_group_size: 0,
+        ..ParquetOptions::default()
+    };
+    assert!(options.validate().is_err());
+    assert!(
+        initialize_outputs_parquet(basedir.path(), &options, &SchemaOptions::default()).is_err()
+    );
+}
+
+fn num_rows(path: &Path) -> usize {
+    let file = File::open(path).unwrap();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    builder.metadata().file_metadata().num_rows() as usize
+}
+
+#[test]
+fn writes_extra_property_tables() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs_parquet(
+        basedir.path(),
+        &ParquetOptions::default(),
+        &SchemaOptions::default(),
+    )
+    .unwrap();
+    let mut mapping = PropertyMapping::default();
+    mapping.insert("NCBI_taxonomy_id", "taxon").unwrap();
+    let options = RunOptions {
+        parse: ParseOptions {
+            properties: mapping,
+            ..ParseOptions::default()
+        },
+        ..RunOptions::default()
+    };
+    let input = open_inputs(&["tests/data/uniparc_sample.xml"]).unwrap();
+    run_inputs(input, handlers, options).unwrap();
+
+    assert!(num_rows(&basedir.path().join("taxon.parquet")) > 0);
+    assert_eq!(
+        num_rows(&basedir.path().join("ncbi_taxonomy_id.parquet")),
+        0
+    );
+}
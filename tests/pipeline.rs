@@ -0,0 +1,148 @@
+extern crate uniparc_xml_parser;
+
+mod common;
+
+use std::fs;
+use std::io::{self, BufReader, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use uniparc_xml_parser::{
+    parse_parallel, run, EntrySplitter, ErrorPolicy, MemorySink, ParseOptions, RunOptions,
+};
+
+const BAD_ENTRY: &str = r#"<entry dataset="uniparc">
+<accession>UPI0000000BAD</accession>
+<sequence length="abc" checksum="0000000000000000">MKV</sequence>
+</entry>"#;
+
+/// Input with `copies` copies of the sample entries, each with a unique accession.
+fn large_input(copies: usize, bad_entry_at: Option<usize>) -> String {
+    let sample = fs::read_to_string("tests/data/uniparc_sample.xml").unwrap();
+    let start = sample.find("<entry").unwrap();
+    let end = sample.rfind("</entry>").unwrap() + "</entry>".len();
+    let entries = &sample[start..end];
+
+    let mut input = String::from(&sample[..start]);
+    for i in 0..copies {
+        if bad_entry_at == Some(i) {
+            input.push_str(BAD_ENTRY);
+        }
+        input.push_str(&entries.replace("UPI0000000A", &format!("UPI{:07}A", i)));
+    }
+    input.push_str(&sample[end..]);
+    input
+}
+
+fn parse(input: &str, threads: usize, on_error: ErrorPolicy) -> (usize, MemorySink) {
+    let options = RunOptions {
+        on_error,
+        threads,
+        ..RunOptions::default()
+    };
+    common::parse(input.as_bytes(), options)
+}
+
+#[test]
+fn parallel_output_matches_sequential() {
+    let input = large_input(200, None);
+    let (count, sequential) = parse(&input, 1, ErrorPolicy::Abort);
+    assert_eq!(count, 600);
+    for &threads in &[2, 4, 7] {
+        let (parallel_count, parallel) = parse(&input, threads, ErrorPolicy::Abort);
+        assert_eq!(parallel_count, count);
+        assert_eq!(parallel.uniparc, sequential.uniparc);
+        assert_eq!(parallel.xrefs, sequential.xrefs);
+        assert_eq!(parallel.properties, sequential.properties);
+        assert_eq!(parallel.domains, sequential.domains);
+    }
+}
+
+#[test]
+fn parallel_skip_on_error() {
+    let input = large_input(200, Some(150));
+    let (count, sequential) = parse(&input, 1, ErrorPolicy::Skip);
+    let (parallel_count, parallel) = parse(&input, 4, ErrorPolicy::Skip);
+    assert_eq!(count, 600);
+    assert_eq!(parallel_count, count);
+    assert_eq!(parallel.uniparc, sequential.uniparc);
+}
+
+#[test]
+fn parallel_abort_on_error() {
+    let input = large_input(200, Some(50));
+    let mut sink = MemorySink::new();
+    let options = RunOptions {
+        threads: 4,
+        ..RunOptions::default()
+    };
+    assert!(run(input.as_bytes(), &mut sink, options).is_err());
+    assert_eq!(sink.uniparc.len(), 150);
+    assert_eq!(sink.uniparc.last().unwrap().uniparc_id, "UPI0000049A03");
+}
+
+/// Reader which counts the number of bytes read from it.
+struct CountingReader<'a> {
+    data: &'a [u8],
+    count: &'a AtomicUsize,
+}
+
+impl<'a> Read for CountingReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.data.read(buf)?;
+        self.count.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+#[test]
+fn slow_chunks_do_not_let_the_input_run_ahead() {
+    // The first entry takes much longer to parse than all of the entries after it.
+    let mut input = String::from(
+        "<uniparc>\n<entry dataset=\"uniparc\">\n<accession>UPI0000000S01</accession>\n",
+    );
+    for i in 0..50_000 {
+        input.push_str(&format!(
+            "<dbReference type=\"EMBL\" id=\"CAA{:05}\" version_i=\"1\" active=\"Y\" version=\"1\" created=\"1991-05-19\" last=\"2010-04-02\"/>\n",
+            i
+        ));
+    }
+    input.push_str(
+        "<sequence length=\"3\" checksum=\"0000000000000000\">MKV</sequence>\n</entry>\n",
+    );
+    let slow_entry_end = input.len();
+    for i in 0..50_000 {
+        input.push_str(&format!(
+            "<entry dataset=\"uniparc\">\n<accession>UPI{:07}T</accession>\n<sequence length=\"3\" checksum=\"0000000000000000\">MKV</sequence>\n</entry>\n",
+            i
+        ));
+    }
+    input.push_str("</uniparc>\n");
+
+    let count = AtomicUsize::new(0);
+    let reader = CountingReader {
+        data: input.as_bytes(),
+        count: &count,
+    };
+    let mut read_before_first_entry = None;
+    let mut num_entries = 0;
+    parse_parallel(
+        EntrySplitter::new(BufReader::new(reader)),
+        4,
+        &ParseOptions::default(),
+        |_, result| {
+            result?;
+            read_before_first_entry.get_or_insert(count.load(Ordering::SeqCst));
+            num_entries += 1;
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(num_entries, 50_001);
+    // Only a limited number of chunks is read ahead of the slow one.
+    let read_ahead = read_before_first_entry.unwrap() - slow_entry_end;
+    assert!(
+        read_ahead < (input.len() - slow_entry_end) / 10,
+        "{}",
+        read_ahead
+    );
+}
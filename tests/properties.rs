@@ -0,0 +1,124 @@
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+mod common;
+
+use std::fs;
+
+use uniparc_xml_parser::{
+    initialize_outputs, run, CsvFormat, ParseOptions, PropertyMapping, RunOptions, SchemaOptions,
+    UniparcReader, UnknownProperties,
+};
+
+const ENTRY: &[u8] = br#"<uniparc><entry dataset="uniparc">
+<accession>UPI0000000B01</accession>
+<dbReference type="UniProtKB/Swiss-Prot" id="P69905" version_i="2" active="Y" version="2" created="2005-03-15" last="2020-08-12">
+<property type="NCBI_taxonomy_id" value="9606"/>
+<property type="UniProtKB_isoform" value="P69905-1"/>
+<property type="isoform_name" value="Alpha"/>
+</dbReference>
+<sequence length="3" checksum="0000000000000000">MKV</sequence>
+</entry></uniparc>"#;
+
+fn options(mapping: PropertyMapping) -> ParseOptions {
+    ParseOptions {
+        properties: mapping,
+        ..ParseOptions::default()
+    }
+}
+
+/// Table, property and value of each property in `ENTRY`.
+fn parse(mapping: PropertyMapping) -> Vec<(String, String, String)> {
+    let options = RunOptions {
+        parse: options(mapping),
+        ..RunOptions::default()
+    };
+    let mut properties = common::parse(ENTRY, options)
+        .1
+        .properties
+        .into_iter()
+        .map(|p| (p.table, p.property, p.value))
+        .collect::<Vec<_>>();
+    properties.sort();
+    properties
+}
+
+fn triples(triples: &[(&str, &str, &str)]) -> Vec<(String, String, String)> {
+    triples
+        .iter()
+        .map(|&(a, b, c)| (a.to_string(), b.to_string(), c.to_string()))
+        .collect()
+}
+
+#[test]
+fn unknown_properties_go_to_generic_table() {
+    let expected = triples(&[
+        ("ncbi_taxonomy_id", "ncbi_taxonomy_id", "9606"),
+        ("property", "UniProtKB_isoform", "P69905-1"),
+        ("property", "isoform_name", "Alpha"),
+    ]);
+    assert_eq!(parse(PropertyMapping::default()), expected);
+
+    let entry = UniparcReader::new(ENTRY).next().unwrap().unwrap();
+    assert_eq!(entry.properties.generic.len(), 2);
+    assert!(entry.extra_properties.is_empty());
+}
+
+#[test]
+fn unknown_properties_go_to_per_type_tables() {
+    let mut mapping = PropertyMapping::default();
+    mapping.unknown = UnknownProperties::PerType;
+    let expected = triples(&[
+        ("ncbi_taxonomy_id", "ncbi_taxonomy_id", "9606"),
+        ("property_isoform_name", "isoform_name", "Alpha"),
+        (
+            "property_uniprotkb_isoform",
+            "UniProtKB_isoform",
+            "P69905-1",
+        ),
+    ]);
+    assert_eq!(parse(mapping.clone()), expected);
+
+    let basedir = tempfile::tempdir().unwrap();
+    let sink = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let run_options = RunOptions {
+        parse: options(mapping),
+        ..RunOptions::default()
+    };
+    run(ENTRY, sink, run_options).unwrap();
+    let output = fs::read_to_string(basedir.path().join("property_uniprotkb_isoform.tsv")).unwrap();
+    assert_eq!(
+        output,
+        "\"UPI0000000B01\"\t1\t\"UniProtKB_isoform\"\t\"P69905-1\"\n"
+    );
+    let generic = fs::read_to_string(basedir.path().join("property.tsv")).unwrap();
+    assert_eq!(generic, "");
+}
+
+#[test]
+fn mapped_properties_go_to_configured_tables() {
+    let mut mapping = PropertyMapping::default();
+    mapping.insert("UniProtKB_isoform", "isoform").unwrap();
+    mapping.insert("isoform_name", "protein_name").unwrap();
+    mapping.insert("NCBI_taxonomy_id", "taxon").unwrap();
+    let expected = triples(&[
+        ("isoform", "UniProtKB_isoform", "P69905-1"),
+        ("protein_name", "isoform_name", "Alpha"),
+        ("taxon", "ncbi_taxonomy_id", "9606"),
+    ]);
+    assert_eq!(parse(mapping), expected);
+}
+
+#[test]
+fn invalid_table_names_are_rejected() {
+    let mut mapping = PropertyMapping::default();
+    assert!(mapping.insert("UniProtKB_isoform", "xref").is_err());
+    assert!(mapping.insert("UniProtKB_isoform", "Isoform").is_err());
+    assert!(mapping.insert("UniProtKB_isoform", "").is_err());
+    assert_eq!(mapping, PropertyMapping::default());
+}
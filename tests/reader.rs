@@ -0,0 +1,293 @@
+extern crate chrono;
+extern crate uniparc_xml_parser;
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::BufReader;
+
+use chrono::NaiveDate;
+
+use uniparc_xml_parser::{
+    open_inputs, parse_entry, parse_entry_ref, run_inputs, EntrySplitter, MemorySink,
+    ParseErrorKind, ParseOptions, RawEntry, RunOptions, UniparcEntry, UniparcReader,
+};
+
+fn read_sample() -> Vec<UniparcEntry> {
+    let file = File::open("tests/data/uniparc_sample.xml").unwrap();
+    UniparcReader::new(BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn reads_all_entries() {
+    let entries = read_sample();
+    let uniparc_ids = entries
+        .iter()
+        .map(|e| e.uniparc.uniparc_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        uniparc_ids,
+        vec!["UPI0000000A01", "UPI0000000A02", "UPI0000000A03"]
+    );
+}
+
+#[test]
+fn reads_entry_contents() {
+    let entries = read_sample();
+    let entry = &entries[0];
+
+    assert_eq!(entry.uniparc.sequence_length, 142);
+    assert_eq!(entry.uniparc.sequence.len(), 142);
+    assert_eq!(entry.uniparc.sequence_checksum, "15E13666573BBBAE");
+
+    let db_ids = entry
+        .xrefs
+        .iter()
+        .map(|x| x.db_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        db_ids,
+        vec!["P69905", "1A00", "NP_000549", "ENSP00000251595"]
+    );
+
+    let xref = &entry.xrefs[0];
+    assert_eq!(
+        (xref.version_i, xref.active, xref.version),
+        (2, true, Some(2))
+    );
+    assert_eq!(xref.created, NaiveDate::from_ymd_opt(2005, 3, 15).unwrap());
+    assert_eq!(xref.last, NaiveDate::from_ymd_opt(2020, 8, 12).unwrap());
+    // PDB cross-references do not have a version.
+    assert_eq!(entry.xrefs[1].version, None);
+
+    assert_eq!(entry.properties.pdb_chain.len(), 1);
+    assert_eq!(entry.properties.pdb_chain[0].value, "1A00A");
+    assert_eq!(entry.properties.ncbi_taxonomy_id.len(), 3);
+
+    // The PROSITE match has two locations and is therefore split into two domains.
+    assert_eq!(entry.domains.len(), 4);
+    assert_eq!(entry.domains[2].database_id, "PS01033");
+    assert_eq!(
+        (entry.domains[2].domain_start, entry.domains[2].domain_end),
+        (2, 70)
+    );
+    assert_eq!(
+        (entry.domains[3].domain_start, entry.domains[3].domain_end),
+        (80, 142)
+    );
+}
+
+#[test]
+fn includes_inactive_xrefs() {
+    let file = File::open("tests/data/uniparc_sample.xml").unwrap();
+    let options = ParseOptions {
+        include_inactive: true,
+        ..ParseOptions::default()
+    };
+    let entry = UniparcReader::with_options(BufReader::new(file), options)
+        .next()
+        .unwrap()
+        .unwrap();
+
+    let xrefs = entry
+        .xrefs
+        .iter()
+        .map(|x| (x.xref_id, x.db_id.as_str(), x.active))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        xrefs,
+        vec![
+            (1, "P69905", true),
+            (2, "CAA23748", false),
+            (3, "1A00", true),
+            (4, "NP_000549", true),
+            (5, "ENSP00000251595", true),
+            (6, "Q9BX83", false),
+        ]
+    );
+
+    // Properties of inactive cross-references are kept as well.
+    assert_eq!(entry.properties.ncbi_taxonomy_id.len(), 5);
+    let ncbi_gi = &entry.properties.ncbi_gi[0];
+    assert_eq!((ncbi_gi.xref_id, ncbi_gi.value.as_str()), (2, "31719"));
+    let accession = &entry.properties.uniprot_kb_accession[0];
+    assert_eq!((accession.xref_id, accession.value.as_str()), (6, "Q9BX83"));
+}
+
+#[test]
+fn truncated_input_is_an_error() {
+    let data = b"<uniparc><entry><accession>UPI0000000A01</accession>";
+    let mut reader = UniparcReader::new(&data[..]);
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn unclosed_entry_is_an_error() {
+    let sample = std::fs::read_to_string("tests/data/uniparc_sample.xml").unwrap();
+    let data = sample.replacen("</entry>", "", 1);
+    let mut reader = UniparcReader::new(data.as_bytes());
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.uniparc_id, "UPI0000000A01");
+    assert!(matches!(error.kind, ParseErrorKind::UnexpectedEof));
+    // The following entries are not affected.
+    let uniparc_ids = reader
+        .map(|entry| entry.unwrap().uniparc.uniparc_id)
+        .collect::<Vec<_>>();
+    assert_eq!(uniparc_ids, vec!["UPI0000000A02", "UPI0000000A03"]);
+}
+
+#[test]
+fn nested_entry_is_an_error() {
+    let raw_entry = RawEntry {
+        offset: 0,
+        data: b"<entry><accession>UPI0000000A01</accession><entry><accession>UPI0000000A02</accession></entry>".to_vec(),
+    };
+    let error = parse_entry(&raw_entry, &ParseOptions::default()).unwrap_err();
+    assert_eq!(error.uniparc_id, "UPI0000000A01");
+    match error.kind {
+        ParseErrorKind::UnexpectedElement { element } => assert_eq!(element, "entry"),
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn reads_concatenated_inputs() {
+    let sample = "tests/data/uniparc_sample.xml";
+    let inputs = open_inputs(&[sample, sample]).unwrap();
+    let mut sink = MemorySink::new();
+    let count = run_inputs(inputs, &mut sink, RunOptions::default()).unwrap();
+    assert_eq!(count, 6);
+    assert_eq!(sink.uniparc.len(), 6);
+}
+
+#[test]
+fn invalid_attribute_is_an_error() {
+    let data = br#"<uniparc><entry><accession>UPI0000000A01</accession>
+<signatureSequenceMatch database="Pfam" id="PF00042"><lcn start="27" end="abc"/></signatureSequenceMatch>
+</entry></uniparc>"#;
+    let error = UniparcReader::new(&data[..]).next().unwrap().unwrap_err();
+    assert_eq!(error.uniparc_id, "UPI0000000A01");
+    assert!(error.position > 0);
+    match error.kind {
+        ParseErrorKind::InvalidValue {
+            element,
+            attribute,
+            value,
+        } => {
+            assert_eq!(element, "lcn");
+            assert_eq!(attribute, "end");
+            assert_eq!(value, "abc");
+        }
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn borrowed_entries_match_owned_entries() {
+    let sample = std::fs::read("tests/data/uniparc_sample.xml").unwrap();
+    for raw_entry in EntrySplitter::new(&sample[..]) {
+        let raw_entry = raw_entry.unwrap();
+        let borrowed = parse_entry_ref(&raw_entry, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            borrowed.clone().into_owned(),
+            parse_entry(&raw_entry, &ParseOptions::default()).unwrap()
+        );
+
+        // Plain values are borrowed from the raw XML instead of being copied.
+        assert!(matches!(borrowed.uniparc.uniparc_id, Cow::Borrowed(_)));
+        assert!(matches!(borrowed.uniparc.sequence, Cow::Borrowed(_)));
+        assert!(matches!(
+            borrowed.uniparc.sequence_checksum,
+            Cow::Borrowed(_)
+        ));
+        for xref in &borrowed.xrefs {
+            assert!(matches!(xref.db_id, Cow::Borrowed(_)));
+        }
+        for domain in &borrowed.domains {
+            assert!(matches!(domain.database_id, Cow::Borrowed(_)));
+        }
+    }
+}
+
+#[test]
+fn values_are_borrowed_from_empty_and_non_empty_tags() {
+    let entries: [&[u8]; 2] = [
+        br#"<entry dataset="uniparc"><accession>UPI0000000A01</accession><dbReference type="PDB" id="1A00" version_i="1" active="Y" created="2005-01-01" last="2020-01-01"/><dbReference type="RefSeq" id="NP_000549" version_i="1" active="Y" created="2005-01-01" last="2020-01-01"></dbReference><signatureSequenceMatch database="Pfam" id="PF00042"><ipr name="Globin" id="IPR000971"/><lcn start="1" end="6"/></signatureSequenceMatch><sequence length="6" checksum="0000000000000000">MVLSPA</sequence></entry>"#,
+        br#"<entry dataset="uniparc">
+  <accession>
+    UPI0000000A01
+  </accession>
+  <dbReference type="PDB" id="1A00" version_i="1" active="Y" created="2005-01-01" last="2020-01-01" />
+  <dbReference type="RefSeq" id="NP_000549" version_i="1" active="Y" created="2005-01-01" last="2020-01-01">
+  </dbReference>
+  <signatureSequenceMatch database="Pfam" id="PF00042" >
+    <ipr name="Globin" id="IPR000971" />
+    <lcn start="1" end="6" />
+  </signatureSequenceMatch>
+  <sequence length="6" checksum="0000000000000000" >
+    MVLSPA
+  </sequence>
+</entry>"#,
+    ];
+    for data in entries.iter() {
+        let raw_entry = RawEntry {
+            offset: 0,
+            data: data.to_vec(),
+        };
+        let entry = parse_entry_ref(&raw_entry, &ParseOptions::default()).unwrap();
+        assert_eq!(entry.uniparc.uniparc_id, "UPI0000000A01");
+        assert!(matches!(entry.uniparc.uniparc_id, Cow::Borrowed(_)));
+        assert_eq!(entry.uniparc.sequence, "MVLSPA");
+        assert!(matches!(entry.uniparc.sequence, Cow::Borrowed(_)));
+        assert!(matches!(entry.uniparc.sequence_checksum, Cow::Borrowed(_)));
+        let db_ids: Vec<_> = entry.xrefs.iter().map(|xref| &xref.db_id).collect();
+        assert_eq!(db_ids, ["1A00", "NP_000549"]);
+        for db_id in db_ids {
+            assert!(matches!(db_id, Cow::Borrowed(_)));
+        }
+        assert_eq!(entry.domains.len(), 1);
+        assert!(matches!(entry.domains[0].database_id, Cow::Borrowed(_)));
+    }
+}
+
+#[test]
+fn escaped_values_are_unescaped() {
+    let raw_entry = RawEntry {
+        offset: 0,
+        data: br#"<entry dataset="uniparc">
+<accession>UPI0000000A01</accession>
+<dbReference type="UniProtKB/Swiss-Prot" id="P69905" version_i="1" active="Y" version="1" created="2005-01-01" last="2020-01-01">
+<property type="protein_name" value="Hemoglobin &amp; &quot;friends&quot;"/>
+</dbReference>
+<sequence length="6" checksum="0000000000000000">
+MVLSPA
+</sequence>
+</entry>"#
+            .to_vec(),
+    };
+    let entry = parse_entry_ref(&raw_entry, &ParseOptions::default()).unwrap();
+    let property = &entry.properties.protein_name[0];
+    assert_eq!(property.value, "Hemoglobin & \"friends\"");
+    assert!(matches!(property.value, Cow::Owned(_)));
+    assert_eq!(entry.uniparc.sequence, "MVLSPA");
+    assert!(matches!(entry.uniparc.sequence, Cow::Borrowed(_)));
+}
+
+#[test]
+fn invalid_date_is_an_error() {
+    let data = br#"<uniparc><entry><accession>UPI0000000A01</accession>
+<dbReference type="EMBL" id="CAA23748" version_i="1" active="Y" version="1" created="1991-13-19" last="2010-04-02"/>
+</entry></uniparc>"#;
+    let error = UniparcReader::new(&data[..]).next().unwrap().unwrap_err();
+    match error.kind {
+        ParseErrorKind::InvalidValue {
+            attribute, value, ..
+        } => assert_eq!(
+            (attribute.as_str(), value.as_str()),
+            ("created", "1991-13-19")
+        ),
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
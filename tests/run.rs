@@ -0,0 +1,139 @@
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+use std::fs;
+use std::path::Path;
+
+use uniparc_xml_parser::{
+    initialize_outputs, open_inputs, run, run_inputs, CsvFormat, ErrorPolicy, MemorySink,
+    ParseError, ParseErrorKind, Quarantine, RunOptions, SchemaOptions,
+};
+
+const BAD_ENTRY: &str = r#"<entry dataset="uniparc">
+<accession>UPI0000000BAD</accession>
+<signatureSequenceMatch database="Pfam" id="PF00042">
+<lcn start="27" end="abc"/>
+</signatureSequenceMatch>
+<sequence length="3" checksum="0000000000000000">MKV</sequence>
+</entry>"#;
+
+/// Sample input with a malformed entry inserted after the first entry.
+fn sample_with_bad_entry() -> String {
+    let sample = fs::read_to_string("tests/data/uniparc_sample.xml").unwrap();
+    let split_at = sample.find("</entry>").unwrap() + "</entry>\n".len();
+    format!(
+        "{}{}\n{}",
+        &sample[..split_at],
+        BAD_ENTRY,
+        &sample[split_at..]
+    )
+}
+
+fn count_lines(path: &Path) -> usize {
+    fs::read_to_string(path).unwrap().lines().count()
+}
+
+#[test]
+fn abort_on_error() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let input = sample_with_bad_entry();
+    let result = run(input.as_bytes(), handlers, RunOptions::default());
+    assert!(result.is_err());
+}
+
+#[test]
+fn skip_on_error() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let input = sample_with_bad_entry();
+    let options = RunOptions {
+        on_error: ErrorPolicy::Skip,
+        ..RunOptions::default()
+    };
+    assert_eq!(run(input.as_bytes(), handlers, options).unwrap(), 3);
+    assert_eq!(count_lines(&basedir.path().join("uniparc.tsv")), 3);
+}
+
+#[test]
+fn quarantine_on_error() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let input = sample_with_bad_entry();
+    let options = RunOptions {
+        on_error: ErrorPolicy::Quarantine(Quarantine::create(basedir.path()).unwrap()),
+        ..RunOptions::default()
+    };
+    assert_eq!(run(input.as_bytes(), handlers, options).unwrap(), 3);
+    assert_eq!(count_lines(&basedir.path().join("uniparc.tsv")), 3);
+
+    let rejected = fs::read_to_string(basedir.path().join("rejected.xml")).unwrap();
+    assert_eq!(rejected, format!("{}\n", BAD_ENTRY));
+
+    let reasons = fs::read_to_string(basedir.path().join("rejected.tsv")).unwrap();
+    let fields = reasons.trim_end().split('\t').collect::<Vec<_>>();
+    assert_eq!(fields[0], input.find(BAD_ENTRY).unwrap().to_string());
+    assert_eq!(fields[2], "UPI0000000BAD");
+    assert!(fields[3].contains("'abc'"));
+}
+
+#[test]
+fn truncated_input_does_not_continue_into_the_next_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let sample = fs::read_to_string("tests/data/uniparc_sample.xml").unwrap();
+    // The first input ends in the middle of the sequence of its last entry.
+    let truncated = dir.path().join("truncated.xml");
+    fs::write(&truncated, &sample[..sample.rfind("</sequence>").unwrap()]).unwrap();
+    let paths = [
+        truncated.as_path(),
+        Path::new("tests/data/uniparc_sample.xml"),
+    ];
+
+    let error = run_inputs(
+        open_inputs(&paths).unwrap(),
+        MemorySink::new(),
+        RunOptions::default(),
+    )
+    .unwrap_err();
+    let error = error.downcast::<ParseError>().unwrap();
+    assert_eq!(error.uniparc_id, "UPI0000000A03");
+    assert!(matches!(error.kind, ParseErrorKind::UnexpectedEof));
+
+    let options = RunOptions {
+        on_error: ErrorPolicy::Skip,
+        ..RunOptions::default()
+    };
+    let mut sink = MemorySink::new();
+    let count = run_inputs(open_inputs(&paths).unwrap(), &mut sink, options).unwrap();
+    assert_eq!(count, 5);
+    let uniparc_ids = sink
+        .uniparc
+        .iter()
+        .map(|u| u.uniparc_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        uniparc_ids,
+        vec![
+            "UPI0000000A01",
+            "UPI0000000A02",
+            "UPI0000000A01",
+            "UPI0000000A02",
+            "UPI0000000A03"
+        ]
+    );
+}
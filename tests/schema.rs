@@ -0,0 +1,168 @@
+extern crate csv;
+extern crate serde_json;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+use std::fs::{self, File};
+
+use serde_json::Value;
+use uniparc_xml_parser::{
+    initialize_outputs, initialize_schema, run, table_schemas, CsvFormat, EntryBatcher,
+    ParseOptions, RunOptions, SchemaOptions, SequenceDigests,
+};
+
+#[test]
+fn header_rows_match_schema() {
+    let basedir = tempfile::tempdir().unwrap();
+    let format = CsvFormat {
+        header: true,
+        ..CsvFormat::default()
+    };
+    let handlers = initialize_outputs(
+        basedir.path().to_path_buf(),
+        format,
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let input = File::open("tests/data/uniparc_sample.xml").unwrap();
+    assert_eq!(run(input, handlers, RunOptions::default()).unwrap(), 3);
+
+    for table in table_schemas(&SchemaOptions::default()) {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_path(basedir.path().join(format!("{}.tsv", table.name)))
+            .unwrap();
+        let header = reader.headers().unwrap().clone();
+        let names = table.columns.iter().map(|c| c.name).collect::<Vec<_>>();
+        assert_eq!(header.iter().collect::<Vec<_>>(), names);
+        for record in reader.records() {
+            assert_eq!(record.unwrap().len(), names.len());
+        }
+    }
+
+    let uniparc = fs::read_to_string(basedir.path().join("uniparc.tsv")).unwrap();
+    assert_eq!(uniparc.lines().count(), 4);
+}
+
+#[test]
+fn no_header_rows_by_default() {
+    let basedir = tempfile::tempdir().unwrap();
+    let handlers = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let input = File::open("tests/data/uniparc_sample.xml").unwrap();
+    run(input, handlers, RunOptions::default()).unwrap();
+
+    let uniparc = fs::read_to_string(basedir.path().join("uniparc.tsv")).unwrap();
+    assert!(uniparc.starts_with("\"UPI0000000A01\""));
+}
+
+#[test]
+fn schema_json_describes_tables() {
+    let basedir = tempfile::tempdir().unwrap();
+    initialize_schema(basedir.path(), &SchemaOptions::default()).unwrap();
+    let schema: Value =
+        serde_json::from_reader(File::open(basedir.path().join("schema.json")).unwrap()).unwrap();
+
+    let tables = schema["tables"].as_array().unwrap();
+    assert_eq!(tables.len(), table_schemas(&SchemaOptions::default()).len());
+
+    let xref = tables.iter().find(|t| t["name"] == "xref").unwrap();
+    assert_eq!(
+        xref["primary_key"],
+        serde_json::json!(["uniparc_id", "xref_id"])
+    );
+    assert_eq!(xref["columns"][1]["name"], "xref_id");
+    assert_eq!(xref["columns"][1]["type"], "int64");
+    assert_eq!(xref["foreign_keys"][0]["table"], "uniparc");
+
+    let gene_name = tables.iter().find(|t| t["name"] == "gene_name").unwrap();
+    assert_eq!(gene_name["foreign_keys"][0]["table"], "xref");
+    assert_eq!(
+        gene_name["foreign_keys"][0]["references"],
+        serde_json::json!(["uniparc_id", "xref_id"])
+    );
+}
+
+/// Names of the columns of the `uniparc` table in the TSV output, schema.json and Arrow.
+fn uniparc_columns(digests: SequenceDigests) -> [Vec<String>; 3] {
+    let basedir = tempfile::tempdir().unwrap();
+    let options = RunOptions {
+        parse: ParseOptions {
+            digests,
+            ..ParseOptions::default()
+        },
+        ..RunOptions::default()
+    };
+    let schema = options.parse.schema();
+    let format = CsvFormat {
+        header: true,
+        ..CsvFormat::default()
+    };
+    let handlers =
+        initialize_outputs(basedir.path().to_path_buf(), format, schema.clone()).unwrap();
+    run(
+        File::open("tests/data/uniparc_sample.xml").unwrap(),
+        handlers,
+        options,
+    )
+    .unwrap();
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .from_path(basedir.path().join("uniparc.tsv"))
+        .unwrap();
+    let tsv: Vec<String> = reader.headers().unwrap().iter().map(String::from).collect();
+    for record in reader.records() {
+        assert_eq!(record.unwrap().len(), tsv.len());
+    }
+
+    initialize_schema(basedir.path(), &schema).unwrap();
+    let json: Value =
+        serde_json::from_reader(File::open(basedir.path().join("schema.json")).unwrap()).unwrap();
+    let uniparc = json["tables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "uniparc")
+        .unwrap();
+    let json = uniparc["columns"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap().to_string())
+        .collect();
+
+    let batcher = EntryBatcher::with_schema(schema, 1024);
+    let (_, arrow) = batcher
+        .schemas()
+        .into_iter()
+        .find(|&(name, _)| name == "uniparc")
+        .unwrap();
+    let arrow = arrow.fields().iter().map(|f| f.name().clone()).collect();
+    [tsv, json, arrow]
+}
+
+#[test]
+fn only_enabled_digests_have_columns() {
+    let base = [
+        "uniparc_id",
+        "sequence",
+        "sequence_length",
+        "sequence_checksum",
+    ];
+    for columns in uniparc_columns(SequenceDigests::default()).iter() {
+        assert_eq!(columns, &base);
+    }
+
+    let mut digests = SequenceDigests::default();
+    digests.enable("refget");
+    digests.enable("md5");
+    let mut expected = base.to_vec();
+    expected.extend(&["sequence_md5", "sequence_refget"]);
+    for columns in uniparc_columns(digests).iter() {
+        assert_eq!(columns, &expected);
+    }
+}
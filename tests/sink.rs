@@ -0,0 +1,212 @@
+extern crate flate2;
+extern crate parquet;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Read};
+
+use flate2::read::GzDecoder;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use uniparc_xml_parser::{
+    initialize_outputs, initialize_outputs_compressed, initialize_outputs_parquet, open_inputs,
+    run_inputs, CsvFormat, MemorySink, ParquetOptions, RunOptions, SchemaOptions, Sink, TsvSink,
+    Uniparc, UniparcDomain, UniparcProperty, UniparcXRef,
+};
+
+/// Sink which counts the rows of each table.
+#[derive(Default)]
+struct CountingSink {
+    uniparc: usize,
+    xrefs: usize,
+    properties: usize,
+    domains: usize,
+    finished: bool,
+}
+
+impl Sink for CountingSink {
+    fn on_uniparc(&mut self, _: &Uniparc) -> Result<(), Box<dyn Error>> {
+        self.uniparc += 1;
+        Ok(())
+    }
+
+    fn on_xref(&mut self, _: &UniparcXRef) -> Result<(), Box<dyn Error>> {
+        self.xrefs += 1;
+        Ok(())
+    }
+
+    fn on_property(&mut self, _: &UniparcProperty) -> Result<(), Box<dyn Error>> {
+        self.properties += 1;
+        Ok(())
+    }
+
+    fn on_domain(&mut self, _: &UniparcDomain) -> Result<(), Box<dyn Error>> {
+        self.domains += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn Error>> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+fn count_lines(path: &std::path::Path) -> usize {
+    fs::read_to_string(path).unwrap().lines().count()
+}
+
+#[test]
+fn memory_sink_collects_rows() {
+    let mut sink = MemorySink::new();
+    let input = open_inputs(&["tests/data/uniparc_sample.xml"]).unwrap();
+    assert_eq!(
+        run_inputs(input, &mut sink, RunOptions::default()).unwrap(),
+        3
+    );
+
+    let ids = sink
+        .uniparc
+        .iter()
+        .map(|u| u.uniparc_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["UPI0000000A01", "UPI0000000A02", "UPI0000000A03"]);
+    assert_eq!(sink.xrefs.len(), 6);
+    assert_eq!(sink.domains.len(), 5);
+    for property in &sink.properties {
+        assert!(sink
+            .xrefs
+            .iter()
+            .any(|x| x.uniparc_id == property.uniparc_id && x.xref_id == property.xref_id));
+    }
+}
+
+#[test]
+fn composed_sinks_receive_all_rows() {
+    let basedir = tempfile::tempdir().unwrap();
+    let tsv = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let parquet = initialize_outputs_parquet(
+        basedir.path(),
+        &ParquetOptions::default(),
+        &SchemaOptions::default(),
+    )
+    .unwrap();
+    let mut counts = CountingSink::default();
+    let sinks: Vec<Box<dyn Sink>> = vec![Box::new(tsv), Box::new(parquet)];
+    let input = open_inputs(&["tests/data/uniparc_sample.xml"]).unwrap();
+    assert_eq!(
+        run_inputs(input, (sinks, &mut counts), RunOptions::default()).unwrap(),
+        3
+    );
+
+    assert!(counts.finished);
+    assert_eq!(counts.uniparc, 3);
+    assert_eq!(counts.xrefs, 6);
+    assert_eq!(counts.domains, 5);
+    assert_eq!(count_lines(&basedir.path().join("xref.tsv")), counts.xrefs);
+
+    let file = File::open(basedir.path().join("xref.parquet")).unwrap();
+    let reader = SerializedFileReader::new(file).unwrap();
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 6);
+
+    let num_properties: usize = [
+        "component",
+        "gene_name",
+        "ncbi_gi",
+        "ncbi_taxonomy_id",
+        "pdb_chain",
+        "protein_name",
+        "proteome_id",
+        "uniprot_kb_accession",
+    ]
+    .iter()
+    .map(|name| count_lines(&basedir.path().join(format!("{}.tsv", name))))
+    .sum();
+    assert_eq!(num_properties, counts.properties);
+}
+
+#[test]
+fn extra_property_tables_are_created() {
+    let basedir = tempfile::tempdir().unwrap();
+    let mut tsv = initialize_outputs(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let property = UniparcProperty {
+        uniparc_id: String::from("UPI0000000A01"),
+        xref_id: 1,
+        property: String::from("not_a_property"),
+        value: String::from("value"),
+        rank: None,
+        lineage: None,
+        table: String::from("property_not_a_property"),
+    };
+    tsv.on_property(&property).unwrap();
+    tsv.finish().unwrap();
+    let output = fs::read_to_string(basedir.path().join("property_not_a_property.tsv")).unwrap();
+    assert_eq!(
+        output,
+        "\"UPI0000000A01\"\t1\t\"not_a_property\"\t\"value\"\n"
+    );
+}
+
+#[test]
+fn output_errors_are_returned() {
+    let basedir = tempfile::tempdir().unwrap();
+    let missing = basedir.path().join("missing");
+    assert!(initialize_outputs(missing, CsvFormat::default(), SchemaOptions::default()).is_err());
+
+    // Outputs of extra property tables are only created when they are first written to.
+    let create = |name: &str| match name {
+        "property_not_a_property" => Err(io::Error::new(io::ErrorKind::Other, "no space left")),
+        _ => Ok(io::sink()),
+    };
+    let mut tsv = TsvSink::new(create, CsvFormat::default(), SchemaOptions::default()).unwrap();
+    let property = UniparcProperty {
+        uniparc_id: String::from("UPI0000000A01"),
+        xref_id: 1,
+        property: String::from("not_a_property"),
+        value: String::from("value"),
+        rank: None,
+        lineage: None,
+        table: String::from("property_not_a_property"),
+    };
+    let error = tsv.on_property(&property).unwrap_err();
+    assert_eq!(error.to_string(), "no space left");
+}
+
+#[test]
+fn compressed_outputs_are_complete_after_finish() {
+    let basedir = tempfile::tempdir().unwrap();
+    let mut tsv = initialize_outputs_compressed(
+        basedir.path().to_path_buf(),
+        CsvFormat::default(),
+        SchemaOptions::default(),
+    )
+    .unwrap();
+    let input = open_inputs(&["tests/data/uniparc_sample.xml"]).unwrap();
+    run_inputs(input, &mut tsv, RunOptions::default()).unwrap();
+
+    // The sink has not been dropped yet, so the files have to be complete after `finish`.
+    let mut num_files = 0;
+    for file in fs::read_dir(basedir.path()).unwrap() {
+        let path = file.unwrap().path();
+        let mut output = String::new();
+        GzDecoder::new(File::open(&path).unwrap())
+            .read_to_string(&mut output)
+            .unwrap();
+        if path.ends_with("uniparc.tsv.gz") {
+            assert_eq!(output.lines().count(), 3);
+        }
+        num_files += 1;
+    }
+    assert!(num_files > 3);
+    drop(tsv);
+}
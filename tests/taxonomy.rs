@@ -0,0 +1,191 @@
+extern crate arrow_array;
+extern crate parquet;
+extern crate tempfile;
+extern crate uniparc_xml_parser;
+
+mod common;
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::StringArray;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use uniparc_xml_parser::{
+    initialize_outputs_parquet, run, FilterScope, ParquetOptions, ParseOptions, RunOptions,
+    TaxonFilter, Taxonomy,
+};
+
+fn taxonomy() -> Taxonomy {
+    Taxonomy::load(Path::new("tests/data/taxdump")).unwrap()
+}
+
+#[test]
+fn reads_taxdump() {
+    let taxonomy = taxonomy();
+    assert_eq!(taxonomy.len(), 16);
+    assert_eq!(taxonomy.name(9606), Some("Homo sapiens"));
+    assert_eq!(taxonomy.rank(9606), Some("species"));
+    assert_eq!(taxonomy.parent(9606), Some(9605));
+    assert_eq!(taxonomy.find("Mammalia"), Some(40674));
+    assert_eq!(taxonomy.find("human"), None);
+    assert_eq!(taxonomy.lineage(562), vec![131567, 2, 91347, 561, 562]);
+    assert_eq!(
+        &*taxonomy.lineage_names(562),
+        "cellular organisms; Bacteria; Enterobacterales; Escherichia; Escherichia coli"
+    );
+    // Lineages are built once and shared afterwards, also with those of their descendants.
+    assert_eq!(
+        &*taxonomy.lineage_names(561),
+        "cellular organisms; Bacteria; Enterobacterales; Escherichia"
+    );
+    assert!(Arc::ptr_eq(
+        &taxonomy.lineage_names(562),
+        &taxonomy.lineage_names(562)
+    ));
+    assert!(Arc::ptr_eq(
+        &taxonomy.shared_rank(9606).unwrap(),
+        &taxonomy.shared_rank(562).unwrap()
+    ));
+    assert!(taxonomy.lineage_names(12345).is_empty());
+    assert!(taxonomy.lineage(12345).is_empty());
+}
+
+#[test]
+fn expands_descendants() {
+    let taxonomy = taxonomy();
+    let mut taxa = taxonomy
+        .descendants(vec![40674])
+        .into_iter()
+        .collect::<Vec<_>>();
+    taxa.sort();
+    assert_eq!(taxa, vec![9443, 9605, 9606, 9989, 10088, 10090, 40674]);
+}
+
+#[test]
+fn filters_by_ancestor() {
+    let taxonomy = taxonomy();
+    let taxa = taxonomy.descendants(taxonomy.find("Enterobacterales"));
+    let options = ParseOptions {
+        taxa: Some(TaxonFilter::new(taxa, FilterScope::Entries)),
+        ..ParseOptions::default()
+    };
+    let sink = common::parse_sample(options, 1);
+    assert_eq!(sink.uniparc.len(), 1);
+    assert_eq!(sink.uniparc[0].uniparc_id, "UPI0000000A03");
+}
+
+#[test]
+fn adds_rank_and_lineage() {
+    let sink = common::parse_sample(ParseOptions::default(), 1);
+    assert!(sink.properties.iter().all(|p| p.rank.is_none()));
+
+    let options = ParseOptions {
+        taxonomy: Some(Arc::new(taxonomy())),
+        ..ParseOptions::default()
+    };
+    let sink = common::parse_sample(options, 1);
+    let taxa = sink
+        .properties
+        .iter()
+        .filter(|p| p.property == "ncbi_taxonomy_id")
+        .map(|p| {
+            (
+                &p.value[..],
+                p.rank.as_deref().unwrap(),
+                p.lineage.as_deref().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(taxa.len(), 5);
+    assert_eq!(taxa[3].0, "10090");
+    assert_eq!(taxa[3].1, "species");
+    assert!(taxa[3].2.ends_with("Mammalia; Rodentia; Mus; Mus musculus"));
+    assert!(sink
+        .properties
+        .iter()
+        .filter(|p| p.property != "ncbi_taxonomy_id")
+        .all(|p| p.rank.is_none() && p.lineage.is_none()));
+}
+
+/// Write the sample to Parquet files with `options`, returning the number of columns of
+/// each table.
+fn parquet_columns(options: ParseOptions) -> HashMap<String, usize> {
+    let basedir = tempfile::tempdir().unwrap();
+    let schema = options.schema();
+    let sink =
+        initialize_outputs_parquet(basedir.path(), &ParquetOptions::default(), &schema).unwrap();
+    let options = RunOptions {
+        parse: options,
+        ..RunOptions::default()
+    };
+    run(&common::sample()[..], sink, options).unwrap();
+
+    let mut columns = HashMap::new();
+    for entry in fs::read_dir(basedir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        let file = File::open(&path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        let table = path.file_stem().unwrap().to_str().unwrap().to_string();
+        columns.insert(table, builder.schema().fields().len());
+    }
+    columns
+}
+
+#[test]
+fn writes_lineage_columns() {
+    let basedir = tempfile::tempdir().unwrap();
+    let options = ParseOptions {
+        taxonomy: Some(Arc::new(taxonomy())),
+        ..ParseOptions::default()
+    };
+    let sink = initialize_outputs_parquet(
+        basedir.path(),
+        &ParquetOptions::default(),
+        &options.schema(),
+    )
+    .unwrap();
+    let options = RunOptions {
+        parse: options,
+        ..RunOptions::default()
+    };
+    run(&common::sample()[..], sink, options).unwrap();
+
+    let file = File::open(basedir.path().join("ncbi_taxonomy_id.parquet")).unwrap();
+    let batch = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(Result::unwrap)
+        .next()
+        .unwrap();
+    assert_eq!(batch.num_columns(), 6);
+    let ranks = batch
+        .column_by_name("rank")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(ranks.value(0), "species");
+}
+
+#[test]
+fn lineage_columns_follow_the_taxonomy_table() {
+    let columns = parquet_columns(ParseOptions::default());
+    assert_eq!(columns["ncbi_taxonomy_id"], 4);
+    assert_eq!(columns["protein_name"], 4);
+
+    let mut options = ParseOptions {
+        taxonomy: Some(Arc::new(taxonomy())),
+        ..ParseOptions::default()
+    };
+    options
+        .properties
+        .insert("NCBI_taxonomy_id", "taxon")
+        .unwrap();
+    let columns = parquet_columns(options);
+    assert_eq!(columns["taxon"], 6);
+    assert_eq!(columns["ncbi_taxonomy_id"], 4);
+    assert_eq!(columns["protein_name"], 4);
+}